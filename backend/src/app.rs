@@ -13,7 +13,7 @@ pub trait System {
     fn start(&mut self, state: &State) {}
     fn finish(&mut self) {}
     fn update(&mut self, state: &State) {}
-    fn render(&mut self, state: &State, output: &wgpu::SurfaceTexture) {}
+    fn render(&mut self, state: &State, output: &wgpu::Texture) {}
     fn precess(&mut self, event: &winit::event::WindowEvent) -> bool { false }
 }
 
@@ -38,7 +38,7 @@ impl<T> App<T> {
         event_loop.run(move |event, _, control_flow| {
             match event {
                 Event::WindowEvent { window_id, ref event }
-                    if window_id == state.window.id()
+                    if window_id == state.window().id()
                     && !system.precess(event)
                     => {
                     match event {
@@ -48,10 +48,10 @@ impl<T> App<T> {
                         _ => {}
                     }
                 },
-                Event::RedrawRequested(window_id) if window_id == state.window.id() => {
+                Event::RedrawRequested(window_id) if window_id == state.window().id() => {
                     match state.get_screen_texture() {
                         Ok(output) => {
-                            system.render(&state, &output);
+                            system.render(&state, &output.texture);
                             output.present();
                         },
                         // 如果发生上下文丢失，就重新配置 surface
@@ -66,7 +66,7 @@ impl<T> App<T> {
                     system.update(&state);
     
                     // 除非手动请求，否则 RedrawRequested 只会触发一次
-                    state.window.request_redraw();
+                    state.window().request_redraw();
                 }
                 _ => {}
             }
@@ -74,9 +74,26 @@ impl<T> App<T> {
     }
 }
 
+/// Runs one frame of `system` on a headless state and reads it back.
+pub fn render_offscreen<S: System>(system: &mut S, state: &State) -> anyhow::Result<Image> {
+    let texture = state.offscreen_texture().ok_or_else(|| anyhow::anyhow!("Not a headless state!"))?;
+    system.update(state);
+    system.render(state, texture);
+    state.read_offscreen()
+}
+
+/// Where the frames go.
+pub enum Target {
+    Window {
+        window: Window,
+        surface: wgpu::Surface,
+    },
+    /// Renders into a texture without a window, for tests and scripts.
+    Offscreen(wgpu::Texture),
+}
+
 pub struct State {
-    pub window: Window,
-    pub surface: wgpu::Surface,
+    pub target: Target,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
     pub config: wgpu::SurfaceConfiguration,
@@ -104,14 +121,7 @@ impl State {
         //     })
         //     .next()
         //     .unwrap();
-        let (device, queue) = pollster::block_on(adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                features: wgpu::Features::empty(),
-                limits: wgpu::Limits::default(),
-                label: None,
-            },
-            None, // 是否追踪APIg调用路径
-        )).expect("Couldn't create the device!");
+        let (device, queue) = Self::request_device(&adapter).expect("Couldn't create the device!");
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::all(),
@@ -124,11 +134,79 @@ impl State {
         surface.configure(&device, &config);
 
         Self {
-            window,
-            surface,
+            target: Target::Window { window, surface },
+            device,
+            queue,
+            config,
+        }
+    }
+
+    /// Creates a state rendering into an offscreen texture, preferring the software adapter.
+    pub fn new_headless(width: u32, height: u32) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let request = |force_fallback_adapter| pollster::block_on(instance.request_adapter(
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter,
+            }
+        ));
+        let adapter = request(true)
+            .or_else(|| request(false))
+            .ok_or_else(|| anyhow::anyhow!("Couldn't create the adapter!"))?;
+        let (device, queue) = Self::request_device(&adapter)?;
+
+        let config = wgpu::SurfaceConfiguration {
+            usage: Self::OFFSCREEN_USAGES,
+            // The effects pass writes BGRA pixels, like most surfaces use.
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        let texture = Self::create_offscreen_texture(&device, &config);
+
+        Ok(Self {
+            target: Target::Offscreen(texture),
             device,
             queue,
             config,
+        })
+    }
+
+    const OFFSCREEN_USAGES: wgpu::TextureUsages = wgpu::TextureUsages::RENDER_ATTACHMENT
+        .union(wgpu::TextureUsages::TEXTURE_BINDING)
+        .union(wgpu::TextureUsages::COPY_SRC)
+        .union(wgpu::TextureUsages::COPY_DST);
+
+    fn request_device(adapter: &wgpu::Adapter) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+        pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                label: None,
+            },
+            None, // 是否追踪APIg调用路径
+        ))
+    }
+
+    fn create_offscreen_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen texture"),
+            size: wgpu::Extent3d { width: config.width, height: config.height, depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+        })
+    }
+
+    /// The window of a windowed state.
+    pub fn window(&self) -> &Window {
+        match &self.target {
+            Target::Window { window, .. } => window,
+            Target::Offscreen(_) => panic!("Headless state has no window!"),
         }
     }
 
@@ -136,7 +214,10 @@ impl State {
         if new_size.width * new_size.height > 0 {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            match &mut self.target {
+                Target::Window { surface, .. } => surface.configure(&self.device, &self.config),
+                Target::Offscreen(texture) => *texture = Self::create_offscreen_texture(&self.device, &self.config),
+            }
         }
     }
 
@@ -145,8 +226,94 @@ impl State {
     }
 
     pub fn get_screen_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
-        let output = self.surface.get_current_texture()?;
-        
-        Ok(output)
+        match &self.target {
+            Target::Window { surface, .. } => surface.get_current_texture(),
+            Target::Offscreen(_) => Err(wgpu::SurfaceError::Lost),
+        }
+    }
+
+    /// The texture of a headless state.
+    pub fn offscreen_texture(&self) -> Option<&wgpu::Texture> {
+        match &self.target {
+            Target::Offscreen(texture) => Some(texture),
+            Target::Window { .. } => None,
+        }
+    }
+
+    /// Copies the offscreen texture back into memory.
+    pub fn read_offscreen(&self) -> anyhow::Result<Image> {
+        const PIXEL_SIZE: u32 = std::mem::size_of::<u32>() as u32;
+        const ALIGN: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let texture = self.offscreen_texture().ok_or_else(|| anyhow::anyhow!("Not a headless state!"))?;
+        let (width, height) = (self.config.width, self.config.height);
+        let unpadded_bytes_per_row = PIXEL_SIZE * width;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(ALIGN) * ALIGN;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: std::num::NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| sender.send(result).unwrap_or(()));
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+
+        let bgra = matches!(self.config.format, wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb);
+        let pixels = slice.get_mapped_range()
+            .chunks(padded_bytes_per_row as usize)
+            .flat_map(|row| row[..unpadded_bytes_per_row as usize].chunks(4))
+            .flat_map(|pixel| if bgra { [pixel[2], pixel[1], pixel[0], pixel[3]] } else { [pixel[0], pixel[1], pixel[2], pixel[3]] })
+            .collect();
+        buffer.unmap();
+
+        Ok(Image { width, height, pixels })
+    }
+}
+
+/// RGBA8 pixels, row by row from the top.
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    pub fn write_png<W: std::io::Write>(&self, writer: W) -> anyhow::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    pub fn save_png(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        self.write_png(std::io::BufWriter::new(std::fs::File::create(path)?))
     }
 }
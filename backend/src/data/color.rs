@@ -1,7 +1,15 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Rgba8(u32);
 
 impl Rgba8 {
+    pub const fn new(rgba: u32) -> Self {
+        Self(rgba)
+    }
+
+    pub fn from_array(rgba: [u8; 4]) -> Self {
+        Self(u32::from_be_bytes(rgba))
+    }
+
     pub fn r(&self) -> u8 {
         (self.0 >> 24) as u8
     }
@@ -35,5 +43,6 @@ mod test_color {
         assert_eq!(rgba.b(), 0xcc);
         assert_eq!(rgba.a(), 0x77);
         assert_eq!(rgba.to_array(), [0xaa, 0xbb, 0xcc, 0x77]);
+        assert_eq!(Rgba8::from_array(rgba.to_array()), rgba);
     }
 }
\ No newline at end of file
@@ -0,0 +1,679 @@
+use std::f32::consts::TAU;
+
+use super::prelude::*;
+use super::graph::{ on_circle, clamp_to_arc };
+use super::intersection::slots;
+use super::fold::Fold;
+use super::exact::{ Exact, ExactGraph };
+use super::neusis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Point,
+    Line,
+    Circle,
+}
+
+/// How an object is made from the objects before it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Definition {
+    FreePoint(WorldPoint),
+    /// A point bound to a line or circle. `param` is the position along the line,
+    /// 0 at `from` and 1 at `to`, or the angle on the circle in radians. On a ray,
+    /// a segment or an arc it is held within them.
+    PointOn { object: ObjectId, param: f32 },
+    /// The common point of `a` and `b` in the `index`-th slot given by [`slots`]. It is
+    /// undefined while that point lies off a ray, segment or arc.
+    Intersection { a: ObjectId, b: ObjectId, index: usize },
+    Line { from: ObjectId, to: ObjectId },
+    /// The half line from `from` through `to`.
+    Ray { from: ObjectId, to: ObjectId },
+    Segment { from: ObjectId, to: ObjectId },
+    Circle { center: ObjectId, through: ObjectId },
+    /// A circle around `center` with the distance from `from` to `to` as radius,
+    /// drawn with a rigid compass.
+    CircleRadius { center: ObjectId, from: ObjectId, to: ObjectId },
+    /// The arc around `center` from `from` counterclockwise to the direction of `to`.
+    Arc { center: ObjectId, from: ObjectId, to: ObjectId, region: ArcRegion },
+    /// The `index`-th line through `pole` on which `a` and `b` cut a segment as long as
+    /// `from` to `to`, drawn with a marked ruler.
+    Neusis { from: ObjectId, to: ObjectId, pole: ObjectId, a: ObjectId, b: ObjectId, index: usize },
+    /// A crease made by one of the origami axioms.
+    Fold(Fold),
+}
+
+impl Definition {
+    pub fn kind(&self) -> ObjectKind {
+        match self {
+            Self::FreePoint(_) | Self::PointOn { .. } | Self::Intersection { .. } => ObjectKind::Point,
+            Self::Line { .. } | Self::Ray { .. } | Self::Segment { .. } | Self::Neusis { .. } | Self::Fold(_) => ObjectKind::Line,
+            Self::Circle { .. } | Self::CircleRadius { .. } | Self::Arc { .. } => ObjectKind::Circle,
+        }
+    }
+
+    pub fn dependencies(&self) -> Vec<ObjectId> {
+        match self {
+            Self::FreePoint(_) => vec![],
+            Self::PointOn { object, .. } => vec![*object],
+            Self::Intersection { a, b, .. } => vec![*a, *b],
+            Self::Line { from, to } | Self::Ray { from, to } | Self::Segment { from, to } => vec![*from, *to],
+            Self::Circle { center, through } => vec![*center, *through],
+            Self::CircleRadius { center, from, to } | Self::Arc { center, from, to, .. } => vec![*center, *from, *to],
+            Self::Neusis { from, to, pole, a, b, .. } => vec![*from, *to, *pole, *a, *b],
+            Self::Fold(fold) => fold.inputs.clone(),
+        }
+    }
+
+    /// The same definition with the dependencies replaced by `map`.
+    pub fn map_ids<F>(&self, map: F) -> Self
+    where
+        F: Fn(ObjectId) -> ObjectId
+    {
+        match *self {
+            Self::FreePoint(pos) => Self::FreePoint(pos),
+            Self::PointOn { object, param } => Self::PointOn { object: map(object), param },
+            Self::Intersection { a, b, index } => Self::Intersection { a: map(a), b: map(b), index },
+            Self::Line { from, to } => Self::Line { from: map(from), to: map(to) },
+            Self::Ray { from, to } => Self::Ray { from: map(from), to: map(to) },
+            Self::Segment { from, to } => Self::Segment { from: map(from), to: map(to) },
+            Self::Circle { center, through } => Self::Circle { center: map(center), through: map(through) },
+            Self::CircleRadius { center, from, to } => Self::CircleRadius { center: map(center), from: map(from), to: map(to) },
+            Self::Arc { center, from, to, region } => Self::Arc { center: map(center), from: map(from), to: map(to), region },
+            Self::Neusis { from, to, pole, a, b, index } => {
+                Self::Neusis { from: map(from), to: map(to), pole: map(pole), a: map(a), b: map(b), index }
+            },
+            Self::Fold(ref fold) => Self::Fold(fold.map_ids(map)),
+        }
+    }
+
+    /// Computes the graph from the graphs of the dependencies.
+    /// Returns `None` if the object does not exist in this configuration.
+    pub fn evaluate<F>(&self, graph_of: F, tolerance: &Tolerance) -> Option<GraphType>
+    where
+        F: Fn(ObjectId) -> Option<GraphType>
+    {
+        let point_of = |id| match graph_of(id) {
+            Some(GraphType::Point(p)) => Some(p),
+            _ => None,
+        };
+
+        match self {
+            Self::FreePoint(pos) => Some(GraphType::Point(*pos)),
+            Self::PointOn { object, param } => match graph_of(*object)? {
+                graph @ (GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to }) => {
+                    let (lo, hi) = graph.extent();
+                    Some(GraphType::Point(from.lerp(to, param.clamp(lo, hi))))
+                },
+                GraphType::Circle { center, radius } => Some(GraphType::Point(on_circle(center, radius, *param))),
+                GraphType::Arc { center, radius, start, end, .. } => {
+                    Some(GraphType::Point(on_circle(center, radius, clamp_to_arc(*param, start, end))))
+                },
+                GraphType::Point(p) => Some(GraphType::Point(p)),
+            },
+            Self::Intersection { a, b, index } => slots(&graph_of(*a)?, &graph_of(*b)?, tolerance)?
+                .get(*index)
+                .copied()
+                .flatten()
+                .map(GraphType::Point),
+            Self::Line { from, to } | Self::Ray { from, to } | Self::Segment { from, to } => {
+                let (from, to) = (point_of(*from)?, point_of(*to)?);
+                if (to - from).length() <= tolerance.distance {
+                    None
+                } else {
+                    Some(match self {
+                        Self::Ray { .. } => GraphType::Ray { from, to },
+                        Self::Segment { .. } => GraphType::Segment { from, to },
+                        _ => GraphType::Line { from, to },
+                    })
+                }
+            },
+            Self::Circle { center, through } => {
+                let (center, through) = (point_of(*center)?, point_of(*through)?);
+                let radius = (through - center).length();
+                if radius <= tolerance.distance {
+                    None
+                } else {
+                    Some(GraphType::Circle { center, radius })
+                }
+            },
+            Self::CircleRadius { center, from, to } => {
+                let center = point_of(*center)?;
+                let radius = (point_of(*to)? - point_of(*from)?).length();
+                if radius <= tolerance.distance {
+                    None
+                } else {
+                    Some(GraphType::Circle { center, radius })
+                }
+            },
+            Self::Arc { center, from, to, region } => {
+                let (center, from, to) = (point_of(*center)?, point_of(*from)?, point_of(*to)?);
+                let radius = (from - center).length();
+                let start = (from - center).angle_from_x_axis().radians;
+                let sweep = ((to - center).angle_from_x_axis().radians - start).rem_euclid(TAU);
+                // Neither nothing nor the whole circle.
+                let degenerate = [radius, (to - center).length(), sweep * radius, (TAU - sweep) * radius];
+                if degenerate.iter().any(|length| *length <= tolerance.distance) {
+                    None
+                } else {
+                    Some(GraphType::Arc { center, radius, start, end: start + sweep, region: *region })
+                }
+            },
+            Self::Neusis { from, to, pole, a, b, index } => {
+                let length = (point_of(*to)? - point_of(*from)?).length();
+                neusis::lines(length, point_of(*pole)?, &graph_of(*a)?, &graph_of(*b)?, tolerance).get(*index).copied()
+            },
+            Self::Fold(fold) => fold.evaluate(&graph_of, tolerance),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Object {
+    pub definition: Definition,
+    pub style: Style,
+    pub label: Option<String>,
+    /// Intermediate steps of macro tools, kept for the dependencies and the E count but not shown.
+    pub hidden: bool,
+    graph: DirtyFlag<Option<GraphType>>,
+}
+
+impl Object {
+    /// The current graph, `None` if the object does not exist in this configuration.
+    pub fn graph(&self) -> Option<GraphType> {
+        *self.graph.read()
+    }
+}
+
+/// A construction DAG. Every object only depends on objects added before it,
+/// so the ids are a topological order.
+#[derive(Clone)]
+pub struct Construction {
+    objects: Vec<Option<Object>>,
+    revision: u64,
+    pub tolerance: Tolerance,
+    /// Set in exact mode, see [`Construction::set_exact`].
+    exact: Option<Exact>,
+}
+
+impl Construction {
+    pub fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            revision: 0,
+            tolerance: Tolerance::default(),
+            exact: None,
+        }
+    }
+
+    /// Turns the exact mode on or off. In exact mode the graphs are computed in constructible
+    /// numbers, so that tangencies and coinciding points are decided exactly rather than within
+    /// the tolerance. The objects beyond square roots still use the tolerance. Every update
+    /// then recomputes the whole construction, as the exact numbers grow with each square root.
+    pub fn set_exact(&mut self, exact: bool) {
+        if exact == self.exact.is_some() {
+            return;
+        }
+        self.exact = exact.then(Exact::default);
+        self.objects.iter_mut().flatten().for_each(|object| object.graph.set_dirty());
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.exact.is_some()
+    }
+
+    /// The exact graphs, in exact mode.
+    pub fn exact(&self) -> Option<&Exact> {
+        self.exact.as_ref()
+    }
+
+    /// In exact mode, an existing point at exactly the point of `definition`.
+    pub fn find_point(&mut self, definition: &Definition) -> Option<ObjectId> {
+        let exact = self.exact.as_mut()?;
+        let point @ ExactGraph::Point(_) = exact.evaluate(definition)?? else { return None };
+        (0..self.objects.len()).map(ObjectId).find(|id| self.objects[id.0].is_some() && exact.graph(*id) == Some(&point))
+    }
+
+    pub fn add(&mut self, definition: Definition) -> ObjectId {
+        let mut dirty = false;
+        for dep in definition.dependencies() {
+            match self.objects.get_mut(dep.0) {
+                Some(Some(object)) => dirty |= object.graph.is_dirty(),
+                _ => panic!("Undefined object!"),
+            }
+        }
+
+        let id = ObjectId(self.objects.len());
+        let graph = match self.exact.as_mut().map(|exact| (exact.update(id, &definition), &exact.tower)) {
+            Some((Some(graph), tower)) => graph.map(|graph| to_graph(&graph, tower, &definition)),
+            _ => definition.evaluate(|id| self.unchecked_graph(id), &self.tolerance),
+        };
+        let mut graph = DirtyFlag::new(graph);
+        if dirty {
+            graph.set_dirty();
+        }
+
+        self.objects.push(Some(Object { definition, style: Style::default(), label: None, hidden: false, graph }));
+        self.revision += 1;
+        id
+    }
+
+    pub fn get(&self, id: ObjectId) -> Option<&Object> {
+        self.objects.get(id.0).and_then(|object| object.as_ref())
+    }
+
+    pub fn graph(&self, id: ObjectId) -> Option<GraphType> {
+        self.get(id).and_then(|object| object.graph())
+    }
+
+    /// The id the next added object will get.
+    pub fn next_id(&self) -> ObjectId {
+        ObjectId(self.objects.len())
+    }
+
+    /// Removes the objects together with everything depending on them.
+    /// Returns the removed objects in id order.
+    pub fn remove(&mut self, ids: &[ObjectId]) -> Vec<(ObjectId, Object)> {
+        let mut marked = vec![false; self.objects.len()];
+        for id in ids {
+            if let Some(mark) = marked.get_mut(id.0) {
+                *mark = true;
+            }
+        }
+
+        for i in 0..self.objects.len() {
+            if let Some(object) = &self.objects[i] {
+                if object.definition.dependencies().iter().any(|dep| marked[dep.0]) {
+                    marked[i] = true;
+                }
+            }
+        }
+
+        let removed: Vec<_> = marked.iter().enumerate()
+            .filter(|(_, marked)| **marked)
+            .filter_map(|(i, _)| self.objects[i].take().map(|object| (ObjectId(i), object)))
+            .collect();
+        if !removed.is_empty() {
+            self.revision += 1;
+        }
+        removed
+    }
+
+    /// Puts back objects taken by [`Construction::remove`]. They are recomputed in the next update.
+    pub fn insert(&mut self, objects: Vec<(ObjectId, Object)>) {
+        for (id, mut object) in objects {
+            if id.0 >= self.objects.len() {
+                self.objects.resize_with(id.0 + 1, || None);
+            }
+            if self.objects[id.0].is_some() {
+                panic!("Object already exists!");
+            }
+            object.graph.set_dirty();
+            self.objects[id.0] = Some(object);
+        }
+        self.revision += 1;
+    }
+
+    /// Returns the previous style, `None` if the object does not exist.
+    pub fn set_style(&mut self, id: ObjectId, style: Style) -> Option<Style> {
+        let object = self.objects.get_mut(id.0)?.as_mut()?;
+        self.revision += 1;
+        Some(std::mem::replace(&mut object.style, style))
+    }
+
+    /// Returns the previous label, `None` if the object does not exist.
+    pub fn set_label(&mut self, id: ObjectId, label: Option<String>) -> Option<Option<String>> {
+        let object = self.objects.get_mut(id.0)?.as_mut()?;
+        self.revision += 1;
+        Some(std::mem::replace(&mut object.label, label))
+    }
+
+    pub fn set_hidden(&mut self, id: ObjectId, hidden: bool) -> Option<bool> {
+        let object = self.objects.get_mut(id.0)?.as_mut()?;
+        self.revision += 1;
+        Some(std::mem::replace(&mut object.hidden, hidden))
+    }
+
+    /// Finds an object with the same definition.
+    pub fn find(&self, definition: &Definition) -> Option<ObjectId> {
+        self.iter().find(|(_, object)| object.definition == *definition).map(|(id, _)| id)
+    }
+
+    fn unchecked_graph(&self, id: ObjectId) -> Option<GraphType> {
+        self.get(id).and_then(|object| *object.graph.unchecked_read())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ObjectId, &Object)> {
+        self.objects.iter().enumerate().filter_map(|(i, object)| object.as_ref().map(|object| (ObjectId(i), object)))
+    }
+
+    /// Increases whenever an object is added or recomputed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves a free point. The dependents are recomputed in the next [`Construction::update`].
+    /// Returns `false` if `id` is not a free point.
+    pub fn move_point(&mut self, id: ObjectId, pos: WorldPoint) -> bool {
+        match self.objects.get_mut(id.0) {
+            Some(Some(Object { definition: Definition::FreePoint(p), graph, .. })) => {
+                *p = pos;
+                *graph.write() = Some(GraphType::Point(pos));
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Slides a point along its object. The dependents are recomputed in the next [`Construction::update`].
+    /// Returns `false` if `id` is not a point on an object.
+    pub fn set_param(&mut self, id: ObjectId, param: f32) -> bool {
+        match self.objects.get_mut(id.0) {
+            Some(Some(Object { definition: Definition::PointOn { param: p, .. }, graph, .. })) => {
+                *p = param;
+                graph.set_dirty();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Recomputes every object whose dependencies changed, in order.
+    /// Returns the ids of the objects that were recomputed.
+    pub fn update(&mut self) -> Vec<ObjectId> {
+        let mut changed = Vec::new();
+        // The exact graphs all start over with a new tower.
+        let mut exact = match &mut self.exact {
+            Some(exact) if self.objects.iter_mut().flatten().any(|object| object.graph.is_dirty()) => {
+                exact.clear();
+                Some(exact)
+            },
+            _ => None,
+        };
+
+        for i in 0..self.objects.len() {
+            let (done, rest) = self.objects.split_at_mut(i);
+            let Some(object) = rest[0].as_mut() else { continue };
+
+            let deps_dirty = object.definition.dependencies().iter().any(|dep| {
+                done[dep.0].as_mut().is_some_and(|dep| dep.graph.is_dirty())
+            });
+            let exact_graph = exact.as_mut().and_then(|exact| Some((exact.update(ObjectId(i), &object.definition)?, &exact.tower)));
+            if deps_dirty || object.graph.is_dirty() {
+                let graph_of = |id: ObjectId| done[id.0].as_ref().and_then(|object| *object.graph.unchecked_read());
+                *object.graph.write() = match exact_graph {
+                    Some((graph, tower)) => graph.map(|graph| to_graph(&graph, tower, &object.definition)),
+                    None => object.definition.evaluate(graph_of, &self.tolerance),
+                };
+            }
+            if object.graph.is_dirty() {
+                changed.push(ObjectId(i));
+            }
+        }
+
+        changed.iter().for_each(|id| {
+            if let Some(object) = self.objects[id.0].as_mut() {
+                object.graph.clean_flag();
+            }
+        });
+        if !changed.is_empty() {
+            self.revision += 1;
+        }
+        changed
+    }
+}
+
+/// The float graph of an exact one, with the region of an arc from its definition.
+fn to_graph(graph: &ExactGraph, tower: &super::exact::Tower, definition: &Definition) -> GraphType {
+    match (graph.to_graph(tower), definition) {
+        (GraphType::Arc { center, radius, start, end, .. }, Definition::Arc { region, .. }) => {
+            GraphType::Arc { center, radius, start, end, region: *region }
+        },
+        (graph, _) => graph,
+    }
+}
+
+impl Default for Construction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_construction {
+    use super::*;
+
+    fn assert_point(graph: Option<GraphType>, expected: WorldPoint) {
+        match graph {
+            Some(GraphType::Point(pos)) => assert!((pos - expected).length() < 1e-3, "{:?} != {:?}", pos, expected),
+            other => panic!("Expected point, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+        let bisector = c.add(Definition::Line { from: top, to: bottom });
+        let ab = c.add(Definition::Line { from: a, to: b });
+        let mid = c.add(Definition::Intersection { a: bisector, b: ab, index: 0 });
+        let on = c.add(Definition::PointOn { object: ca, param: std::f32::consts::FRAC_PI_2 });
+
+        assert_eq!(c.graph(ca), Some(GraphType::Circle { center: p(0.0, 0.0), radius: 6.0 }));
+        assert_point(c.graph(top), p(3.0, 27.0f32.sqrt()));
+        assert_point(c.graph(bottom), p(3.0, -27.0f32.sqrt()));
+        assert_point(c.graph(mid), p(3.0, 0.0));
+        assert_point(c.graph(on), p(0.0, 6.0));
+        assert_eq!(c.get(bisector).unwrap().definition.kind(), ObjectKind::Line);
+        assert_eq!(c.len(), 10);
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let d = c.add(Definition::FreePoint(p(-8.0, 0.0)));
+        let e = c.add(Definition::FreePoint(p(-4.0, 0.0)));
+        let segment = c.add(Definition::Segment { from: d, to: e });
+        let ray = c.add(Definition::Ray { from: e, to: d });
+        // Only the left point of the circle lies on the segment, and on the ray going left,
+        // where it keeps its slot of the whole line.
+        let x = c.add(Definition::Intersection { a: segment, b: ca, index: 0 });
+        let y = c.add(Definition::Intersection { a: segment, b: ca, index: 1 });
+        let z = c.add(Definition::Intersection { a: ray, b: ca, index: 1 });
+        let behind = c.add(Definition::Intersection { a: ray, b: ca, index: 0 });
+        let on = c.add(Definition::PointOn { object: segment, param: 2.0 });
+
+        assert_eq!(c.graph(segment), Some(GraphType::Segment { from: p(-8.0, 0.0), to: p(-4.0, 0.0) }));
+        assert_eq!(c.get(ray).unwrap().definition.kind(), ObjectKind::Line);
+        assert_point(c.graph(x), p(-6.0, 0.0));
+        assert_eq!(c.graph(y), None);
+        assert_point(c.graph(z), p(-6.0, 0.0));
+        assert_eq!(c.graph(behind), None);
+        assert_point(c.graph(on), p(-4.0, 0.0));
+    }
+
+    #[test]
+    fn test_stable_slots() {
+        let mut c = Construction::new();
+        let o = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let circle = c.add(Definition::Circle { center: o, through: a });
+        let d = c.add(Definition::FreePoint(p(-10.0, 3.0)));
+        let e = c.add(Definition::FreePoint(p(0.0, 3.0)));
+        let segment = c.add(Definition::Segment { from: d, to: e });
+        let left = c.add(Definition::Intersection { a: segment, b: circle, index: 0 });
+        let right = c.add(Definition::Intersection { a: segment, b: circle, index: 1 });
+        let dependent = c.add(Definition::Line { from: o, to: right });
+        assert_point(c.graph(left), p(-4.0, 3.0));
+        assert_eq!(c.graph(right), None);
+
+        // Dragging the end across the right point brings it in, without touching the left one.
+        c.move_point(e, p(10.0, 3.0));
+        c.update();
+        assert_point(c.graph(left), p(-4.0, 3.0));
+        assert_point(c.graph(right), p(4.0, 3.0));
+
+        // Dragging the start across the left point drops only that one.
+        c.move_point(d, p(0.0, 3.0));
+        c.update();
+        assert_eq!(c.graph(left), None);
+        assert_point(c.graph(right), p(4.0, 3.0));
+        assert_eq!(c.graph(dependent), Some(GraphType::Line { from: p(0.0, 0.0), to: p(4.0, 3.0) }));
+    }
+
+    #[test]
+    fn test_arc() {
+        use std::f32::consts::PI;
+        let mut c = Construction::new();
+        let o = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(0.0, 10.0)));
+        // From a counterclockwise to the direction of b, whatever its distance.
+        let arc = c.add(Definition::Arc { center: o, from: a, to: b, region: ArcRegion::Segment });
+        let back = c.add(Definition::Arc { center: o, from: b, to: a, region: ArcRegion::Sector });
+        let on = c.add(Definition::PointOn { object: arc, param: PI });
+        let line = c.add(Definition::Line { from: o, to: a });
+        let x = c.add(Definition::Intersection { a: line, b: arc, index: 0 });
+        let y = c.add(Definition::Intersection { a: line, b: arc, index: 1 });
+        let degenerate = c.add(Definition::Arc { center: o, from: a, to: a, region: ArcRegion::Sector });
+
+        assert_eq!(c.graph(arc), Some(GraphType::Arc { center: p(0.0, 0.0), radius: 5.0, start: 0.0, end: PI / 2.0, region: ArcRegion::Segment }));
+        assert_eq!(c.get(arc).unwrap().definition.kind(), ObjectKind::Circle);
+        let Some(GraphType::Arc { start, end, .. }) = c.graph(back) else { panic!("Expected arc") };
+        assert!((start - PI / 2.0).abs() < 1e-6 && (end - 2.0 * PI).abs() < 1e-6);
+        // A point outside the range moves to the nearer end.
+        assert_point(c.graph(on), p(0.0, 5.0));
+        // The point behind the start keeps its slot empty.
+        assert_eq!(c.graph(x), None);
+        assert_point(c.graph(y), p(5.0, 0.0));
+        assert_eq!(c.graph(degenerate), None);
+    }
+
+    #[test]
+    fn test_exact_mode() {
+        let mut c = Construction::new();
+        let o = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(1.0, 0.0)));
+        let circle = c.add(Definition::Circle { center: o, through: a });
+        // Closer to the circle than the tolerance, but still cutting it twice.
+        let d = c.add(Definition::FreePoint(p(-1.0, 0.9999)));
+        let e = c.add(Definition::FreePoint(p(1.0, 0.9999)));
+        let line = c.add(Definition::Line { from: d, to: e });
+        let first = c.add(Definition::Intersection { a: line, b: circle, index: 0 });
+        let second = c.add(Definition::Intersection { a: line, b: circle, index: 1 });
+        assert_point(c.graph(first), p(0.0, 0.9999));
+        assert_eq!(c.graph(second), None);
+
+        c.set_exact(true);
+        assert_eq!(c.update().len(), c.len());
+        let x = (1.0f64 - 0.9999f32 as f64 * 0.9999f32 as f64).sqrt() as f32;
+        assert_point(c.graph(first), p(-x, 0.9999));
+        assert_point(c.graph(second), p(x, 0.9999));
+
+        // A real tangency still has a single point.
+        c.move_point(d, p(-1.0, 1.0));
+        c.move_point(e, p(1.0, 1.0));
+        c.update();
+        assert_eq!(c.graph(first), Some(GraphType::Point(p(0.0, 1.0))));
+        assert_eq!(c.graph(second), None);
+        assert!(c.exact().unwrap().graph(first).is_some());
+
+        c.set_exact(false);
+        c.update();
+        assert_point(c.graph(first), p(0.0, 1.0));
+    }
+
+    #[test]
+    fn test_move_point() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let d = c.add(Definition::FreePoint(p(0.0, 10.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let ad = c.add(Definition::Line { from: a, to: d });
+
+        assert!(c.move_point(b, p(0.0, 6.0)));
+        assert_eq!(c.update(), vec![b, ca, cb, top]);
+        assert_point(c.graph(top), p(-27.0f32.sqrt(), 3.0));
+        assert_eq!(c.graph(ad), Some(GraphType::Line { from: p(0.0, 0.0), to: p(0.0, 10.0) }));
+
+        // Nothing is recomputed without a change.
+        assert!(c.update().is_empty());
+        // Only free points can be moved.
+        assert!(!c.move_point(top, p(1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_undefined() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(1.0, 0.0)));
+        let d = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let e = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cd = c.add(Definition::Circle { center: d, through: e });
+        let x = c.add(Definition::Intersection { a: ca, b: cd, index: 0 });
+        let line = c.add(Definition::Line { from: x, to: a });
+
+        assert_eq!(c.graph(x), None);
+        assert_eq!(c.graph(line), None);
+
+        c.move_point(e, p(1.0, 0.0));
+        c.update();
+        assert_point(c.graph(x), p(1.0, 0.0));
+        assert!(c.graph(line).is_some());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let d = c.add(Definition::FreePoint(p(0.0, 6.0)));
+        let ab = c.add(Definition::Line { from: a, to: b });
+        let ad = c.add(Definition::Line { from: a, to: d });
+        let x = c.add(Definition::Intersection { a: ab, b: ad, index: 0 });
+        let cb = c.add(Definition::Circle { center: d, through: b });
+
+        let removed = c.remove(&[ab]);
+        assert_eq!(removed.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![ab, x]);
+        assert!(c.get(ab).is_none() && c.get(x).is_none());
+        assert_eq!(c.len(), 5);
+        assert!(c.graph(cb).is_some());
+
+        c.insert(removed);
+        c.update();
+        assert_eq!(c.len(), 7);
+        assert_point(c.graph(x), p(0.0, 0.0));
+        assert_eq!(c.next_id(), ObjectId(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "Read dirtied data!")]
+    fn test_read_before_update() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        c.move_point(a, p(1.0, 0.0));
+        c.graph(a);
+    }
+}
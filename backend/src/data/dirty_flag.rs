@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct DirtyFlag<T> {
     is_dirty: bool,
     data: T,
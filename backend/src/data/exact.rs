@@ -0,0 +1,867 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::Neg,
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{ Zero, One, Signed, ToPrimitive };
+
+use super::prelude::*;
+use super::graph::clamp_to_arc;
+
+/// A constructible number, exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Number {
+    Rational(BigRational),
+    /// `a + b * sqrt(r)`, where `r` is the radicand of `level` in the [`Tower`], and `a` and `b`
+    /// are below that level. `b` is never zero, so that equal numbers have equal forms.
+    Extension { level: usize, a: Box<Number>, b: Box<Number> },
+}
+
+impl Number {
+    pub fn zero() -> Self {
+        Self::Rational(BigRational::zero())
+    }
+
+    pub fn from_int(value: i64) -> Self {
+        Self::Rational(BigRational::from_integer(BigInt::from(value)))
+    }
+
+    /// The exact value of a finite float.
+    pub fn from_f32(value: f32) -> Self {
+        Self::Rational(BigRational::from_float(value).expect("Coordinates must be finite"))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Self::Rational(value) if value.is_zero())
+    }
+
+    fn level(&self) -> Option<usize> {
+        match self {
+            Self::Rational(_) => None,
+            Self::Extension { level, .. } => Some(*level),
+        }
+    }
+
+    fn extension(level: usize, a: Number, b: Number) -> Self {
+        match b.is_zero() {
+            true => a,
+            false => Self::Extension { level, a: Box::new(a), b: Box::new(b) },
+        }
+    }
+}
+
+impl Neg for &Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Rational(value) => Number::Rational(-value),
+            Number::Extension { level, a, b } => Number::Extension { level: *level, a: Box::new(-&**a), b: Box::new(-&**b) },
+        }
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        -&self
+    }
+}
+
+/// The fields the numbers live in: the rationals extended by one square root per level.
+/// No radicand is a square in the field below it, which makes the forms of the numbers unique,
+/// so equality is structural.
+#[derive(Debug, Clone, Default)]
+pub struct Tower {
+    radicands: Vec<Number>,
+}
+
+impl Tower {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of square roots the rationals are extended by.
+    pub fn depth(&self) -> usize {
+        self.radicands.len()
+    }
+
+    /// Drops the square roots from `depth` on. The numbers using them are meaningless after.
+    pub fn truncate(&mut self, depth: usize) {
+        self.radicands.truncate(depth);
+    }
+
+    pub fn add(&self, x: &Number, y: &Number) -> Number {
+        match (x, y) {
+            (Number::Rational(x), Number::Rational(y)) => Number::Rational(x + y),
+            _ => match (x.level().cmp(&y.level()), x, y) {
+                (Ordering::Less, ..) => self.add(y, x),
+                (Ordering::Greater, Number::Extension { level, a, b }, _) => {
+                    Number::Extension { level: *level, a: Box::new(self.add(a, y)), b: b.clone() }
+                },
+                (Ordering::Equal, Number::Extension { level, a: a1, b: b1 }, Number::Extension { a: a2, b: b2, .. }) => {
+                    Number::extension(*level, self.add(a1, a2), self.add(b1, b2))
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    pub fn sub(&self, x: &Number, y: &Number) -> Number {
+        self.add(x, &-y)
+    }
+
+    pub fn mul(&self, x: &Number, y: &Number) -> Number {
+        match (x, y) {
+            (Number::Rational(x), Number::Rational(y)) => Number::Rational(x * y),
+            _ => match (x.level().cmp(&y.level()), x, y) {
+                (Ordering::Less, ..) => self.mul(y, x),
+                (Ordering::Greater, Number::Extension { level, a, b }, _) => {
+                    Number::extension(*level, self.mul(a, y), self.mul(b, y))
+                },
+                (Ordering::Equal, Number::Extension { level, a: a1, b: b1 }, Number::Extension { a: a2, b: b2, .. }) => {
+                    let r = &self.radicands[*level];
+                    Number::extension(
+                        *level,
+                        self.add(&self.mul(a1, a2), &self.mul(&self.mul(b1, b2), r)),
+                        self.add(&self.mul(a1, b2), &self.mul(a2, b1)),
+                    )
+                },
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    /// `None` for zero.
+    pub fn inv(&self, x: &Number) -> Option<Number> {
+        match x {
+            Number::Rational(value) if value.is_zero() => None,
+            Number::Rational(value) => Some(Number::Rational(value.recip())),
+            Number::Extension { level, a, b } => {
+                // Multiply by the conjugate, the norm is in the field below.
+                let r = &self.radicands[*level];
+                let norm = self.inv(&self.sub(&self.mul(a, a), &self.mul(&self.mul(b, b), r)))?;
+                Some(Number::extension(*level, self.mul(a, &norm), -self.mul(b, &norm)))
+            },
+        }
+    }
+
+    pub fn div(&self, x: &Number, y: &Number) -> Option<Number> {
+        Some(self.mul(x, &self.inv(y)?))
+    }
+
+    /// The sign of `x`, as its order to zero.
+    pub fn signum(&self, x: &Number) -> Ordering {
+        match x {
+            Number::Rational(value) => value.cmp(&BigRational::zero()),
+            Number::Extension { level, a, b } => {
+                let (sa, sb) = (self.signum(a), self.signum(b));
+                if sa == Ordering::Equal || sa == sb {
+                    return if sa == Ordering::Equal { sb } else { sa };
+                }
+                // Opposite signs: the larger of |a| and |b| * sqrt(r) wins.
+                let r = &self.radicands[*level];
+                match self.signum(&self.sub(&self.mul(a, a), &self.mul(&self.mul(b, b), r))) {
+                    Ordering::Greater => sa,
+                    _ => sb,
+                }
+            },
+        }
+    }
+
+    pub fn cmp(&self, x: &Number, y: &Number) -> Ordering {
+        self.signum(&self.sub(x, y))
+    }
+
+    pub fn to_f64(&self, x: &Number) -> f64 {
+        match x {
+            Number::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Number::Extension { level, a, b } => self.to_f64(a) + self.to_f64(b) * self.to_f64(&self.radicands[*level]).sqrt(),
+        }
+    }
+
+    /// The nonnegative square root, extending the tower if it isn't in it yet.
+    /// `None` for negative numbers.
+    pub fn sqrt(&mut self, x: &Number) -> Option<Number> {
+        match self.signum(x) {
+            Ordering::Less => None,
+            Ordering::Equal => Some(Number::zero()),
+            Ordering::Greater => {
+                let root = self.try_sqrt(x, self.radicands.len()).unwrap_or_else(|| {
+                    self.radicands.push(x.clone());
+                    Number::Extension { level: self.radicands.len() - 1, a: Box::new(Number::zero()), b: Box::new(Number::from_int(1)) }
+                });
+                Some(match self.signum(&root) {
+                    Ordering::Less => -root,
+                    _ => root,
+                })
+            },
+        }
+    }
+
+    /// A square root of `x` in the field of the first `levels` levels, if there is one.
+    fn try_sqrt(&self, x: &Number, levels: usize) -> Option<Number> {
+        let Some(level) = levels.checked_sub(1) else {
+            let Number::Rational(value) = x else { unreachable!() };
+            let root = |v: &BigInt| (!v.is_negative()).then(|| v.sqrt()).filter(|root| root * root == *v);
+            return Some(Number::Rational(BigRational::new(root(value.numer())?, root(value.denom())?)));
+        };
+
+        let half = Number::Rational(BigRational::new(BigInt::one(), BigInt::from(2)));
+        match x {
+            Number::Extension { level: top, a, b } if *top == level => {
+                // (c + d sqrt(r))^2 = a + b sqrt(r) needs c^2 = (a +- sqrt(a^2 - b^2 r)) / 2 and d = b / 2c.
+                let r = &self.radicands[level];
+                let norm = self.try_sqrt(&self.sub(&self.mul(a, a), &self.mul(&self.mul(b, b), r)), level)?;
+                [norm.clone(), -norm].iter().find_map(|norm| {
+                    let c = self.try_sqrt(&self.mul(&self.add(a, norm), &half), level).filter(|c| !c.is_zero())?;
+                    let d = self.div(&self.mul(b, &half), &c)?;
+                    Some(Number::extension(level, c, d))
+                })
+            },
+            // In the field below, either x or x / r is a square there.
+            _ => self.try_sqrt(x, level).or_else(|| {
+                let root = self.try_sqrt(&self.div(x, &self.radicands[level])?, level)?;
+                Some(Number::extension(level, Number::zero(), root))
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactPoint {
+    pub x: Number,
+    pub y: Number,
+}
+
+impl ExactPoint {
+    pub fn from_world(point: WorldPoint) -> Self {
+        Self { x: Number::from_f32(point.x), y: Number::from_f32(point.y) }
+    }
+
+    pub fn to_world(&self, tower: &Tower) -> WorldPoint {
+        WorldPoint::new(tower.to_f64(&self.x) as f32, tower.to_f64(&self.y) as f32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExactGraph {
+    Point(ExactPoint),
+    Line { from: ExactPoint, to: ExactPoint },
+    /// The half line from `from` through `to`.
+    Ray { from: ExactPoint, to: ExactPoint },
+    Segment { from: ExactPoint, to: ExactPoint },
+    /// The squared radius stays in the field of the points.
+    Circle { center: ExactPoint, radius2: Number },
+    /// The part of the circle counterclockwise from `start` to `end`, both on it.
+    Arc { center: ExactPoint, radius2: Number, start: ExactPoint, end: ExactPoint },
+}
+
+impl ExactGraph {
+    /// The two points a straight graph is drawn through.
+    pub fn line_points(&self) -> Option<(&ExactPoint, &ExactPoint)> {
+        match self {
+            Self::Line { from, to } | Self::Ray { from, to } | Self::Segment { from, to } => Some((from, to)),
+            _ => None,
+        }
+    }
+
+    /// Whether the graph is straight: a line, a ray or a segment.
+    pub fn is_line(&self) -> bool {
+        self.line_points().is_some()
+    }
+
+    /// The graph in floats. Arcs come out as sectors, the region is not part of the exact graph.
+    pub fn to_graph(&self, tower: &Tower) -> GraphType {
+        let angle = |p: &ExactPoint, center: &ExactPoint| {
+            (tower.to_f64(&tower.sub(&p.y, &center.y)) as f32).atan2(tower.to_f64(&tower.sub(&p.x, &center.x)) as f32)
+        };
+        match self {
+            Self::Point(p) => GraphType::Point(p.to_world(tower)),
+            Self::Line { from, to } => GraphType::Line { from: from.to_world(tower), to: to.to_world(tower) },
+            Self::Ray { from, to } => GraphType::Ray { from: from.to_world(tower), to: to.to_world(tower) },
+            Self::Segment { from, to } => GraphType::Segment { from: from.to_world(tower), to: to.to_world(tower) },
+            Self::Circle { center, radius2 } => GraphType::Circle { center: center.to_world(tower), radius: tower.to_f64(radius2).sqrt() as f32 },
+            Self::Arc { center, radius2, start, end } => {
+                let from = angle(start, center);
+                GraphType::Arc {
+                    center: center.to_world(tower),
+                    radius: tower.to_f64(radius2).sqrt() as f32,
+                    start: from,
+                    end: from + (angle(end, center) - from).rem_euclid(std::f32::consts::TAU),
+                    region: ArcRegion::Sector,
+                }
+            },
+        }
+    }
+
+    /// The whole line or circle of a bounded graph, or the graph itself.
+    pub fn carrier(&self) -> Self {
+        match self {
+            Self::Arc { center, radius2, .. } => Self::Circle { center: center.clone(), radius2: radius2.clone() },
+            _ => match self.line_points() {
+                Some((from, to)) => Self::Line { from: from.clone(), to: to.clone() },
+                None => self.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExactIntersection {
+    None,
+    /// In the same order as [`intersect`](super::intersection::intersect).
+    Points(Vec<ExactPoint>),
+    Coincident,
+}
+
+impl ExactIntersection {
+    pub fn points(&self) -> &[ExactPoint] {
+        match self {
+            Self::Points(points) => points,
+            _ => &[],
+        }
+    }
+}
+
+// Vector helpers on points.
+impl Tower {
+    fn vsub(&self, p: &ExactPoint, q: &ExactPoint) -> ExactPoint {
+        ExactPoint { x: self.sub(&p.x, &q.x), y: self.sub(&p.y, &q.y) }
+    }
+
+    /// `p + v * t`
+    fn vmove(&self, p: &ExactPoint, v: &ExactPoint, t: &Number) -> ExactPoint {
+        ExactPoint { x: self.add(&p.x, &self.mul(&v.x, t)), y: self.add(&p.y, &self.mul(&v.y, t)) }
+    }
+
+    fn dot(&self, v: &ExactPoint, w: &ExactPoint) -> Number {
+        self.add(&self.mul(&v.x, &w.x), &self.mul(&v.y, &w.y))
+    }
+
+    fn cross(&self, v: &ExactPoint, w: &ExactPoint) -> Number {
+        self.sub(&self.mul(&v.x, &w.y), &self.mul(&v.y, &w.x))
+    }
+
+    /// Whether `p`, on the line or circle of `graph`, is within the graph.
+    fn covers(&self, graph: &ExactGraph, p: &ExactPoint) -> bool {
+        // Not behind `from` when looking towards `to`.
+        let ahead = |from: &ExactPoint, to: &ExactPoint| {
+            self.signum(&self.dot(&self.vsub(to, from), &self.vsub(p, from))) != Ordering::Less
+        };
+        // `b` is not clockwise from `a`.
+        let left = |a: &ExactPoint, b: &ExactPoint| self.signum(&self.cross(a, b)) != Ordering::Less;
+        match graph {
+            ExactGraph::Ray { from, to } => ahead(from, to),
+            ExactGraph::Segment { from, to } => ahead(from, to) && ahead(to, from),
+            ExactGraph::Arc { center, start, end, .. } => {
+                let (u, v, w) = (self.vsub(start, center), self.vsub(p, center), self.vsub(end, center));
+                // Up to half a turn, both ends bound the arc. Beyond it, either does.
+                let turn = self.signum(&self.cross(&u, &w));
+                let half = turn == Ordering::Greater || turn == Ordering::Equal && self.signum(&self.dot(&u, &w)) == Ordering::Less;
+                match half {
+                    true => left(&u, &v) && left(&v, &w),
+                    false => left(&u, &v) || left(&v, &w),
+                }
+            },
+            _ => true,
+        }
+    }
+
+    /// Whether `p` lies on `graph`.
+    pub fn contains(&self, graph: &ExactGraph, p: &ExactPoint) -> bool {
+        match graph {
+            ExactGraph::Point(q) => p == q,
+            ExactGraph::Line { from, to } | ExactGraph::Ray { from, to } | ExactGraph::Segment { from, to } => {
+                self.cross(&self.vsub(to, from), &self.vsub(p, from)).is_zero() && self.covers(graph, p)
+            },
+            ExactGraph::Circle { center, radius2 } | ExactGraph::Arc { center, radius2, .. } => {
+                let v = self.vsub(p, center);
+                self.dot(&v, &v) == *radius2 && self.covers(graph, p)
+            },
+        }
+    }
+
+    /// Whether `a` and `b` are the same object. Bounded graphs are when they overlap on
+    /// the same line or circle, as they coincide for [`intersect`](super::intersection::intersect).
+    pub fn same(&self, a: &ExactGraph, b: &ExactGraph) -> bool {
+        use ExactGraph::*;
+
+        match (a, b) {
+            (Line { .. } | Ray { .. } | Segment { .. }, Line { from, to } | Ray { from, to } | Segment { from, to }) => {
+                let line = a.carrier();
+                self.contains(&line, from) && self.contains(&line, to) && self.overlap(a, b).is_none()
+            },
+            (
+                Circle { center: c1, radius2: r1 } | Arc { center: c1, radius2: r1, .. },
+                Circle { center: c2, radius2: r2 } | Arc { center: c2, radius2: r2, .. },
+            ) => c1 == c2 && r1 == r2 && self.arc_overlap(a, b).is_none(),
+            _ => a == b,
+        }
+    }
+
+    /// Empties the slots of the points, found on the lines of `a` and `b`, which are off the graphs.
+    fn within(&self, points: Vec<ExactPoint>, a: &ExactGraph, b: &ExactGraph) -> Option<Vec<Option<ExactPoint>>> {
+        Some(points.into_iter().map(|p| (self.covers(a, &p) && self.covers(b, &p)).then_some(p)).collect())
+    }
+
+    /// The common part of two straight graphs on the same line: the single end where they
+    /// meet in one slot, or `None` when they share more.
+    fn overlap(&self, a: &ExactGraph, b: &ExactGraph) -> Option<Vec<Option<ExactPoint>>> {
+        let (Some((f1, t1)), Some((f2, t2))) = (a.line_points(), b.line_points()) else { return Some(Vec::new()) };
+        let d = self.vsub(t1, f1);
+        let along = |p: &ExactPoint| self.dot(&self.vsub(p, f1), &d);
+        // The lower and upper ends of each graph along `a`, `None` where it goes on.
+        let ends = |graph: &ExactGraph, from: &ExactPoint, to: &ExactPoint| {
+            let (s0, s1) = (along(from), along(to));
+            let forward = self.cmp(&s0, &s1) == Ordering::Less;
+            let (first, last) = match graph {
+                ExactGraph::Ray { .. } => (Some((s0, from.clone())), None),
+                ExactGraph::Segment { .. } => (Some((s0, from.clone())), Some((s1, to.clone()))),
+                _ => (None, None),
+            };
+            if forward { (first, last) } else { (last, first) }
+        };
+        let pick = |x: Option<(Number, ExactPoint)>, y: Option<(Number, ExactPoint)>, order: Ordering| match (x, y) {
+            (Some(x), Some(y)) => Some(if self.cmp(&x.0, &y.0) == order { x } else { y }),
+            (x, y) => x.or(y),
+        };
+        let ((lo1, hi1), (lo2, hi2)) = (ends(a, f1, t1), ends(b, f2, t2));
+        match (pick(lo1, lo2, Ordering::Greater), pick(hi1, hi2, Ordering::Less)) {
+            (Some((lo, end)), Some((hi, _))) => match self.cmp(&lo, &hi) {
+                Ordering::Greater => Some(vec![None]),
+                Ordering::Equal => Some(vec![Some(end)]),
+                Ordering::Less => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The common part of two circles or arcs on the same circle, in the slots of the float
+    /// `arc_overlap`: `None` when they share more than their ends.
+    fn arc_overlap(&self, a: &ExactGraph, b: &ExactGraph) -> Option<Vec<Option<ExactPoint>>> {
+        // A whole circle holds any arc of it.
+        let (ExactGraph::Arc { start: s1, end: e1, .. }, ExactGraph::Arc { start: s2, end: e2, .. }) = (a, b) else {
+            return None;
+        };
+        // One starts within the other.
+        if self.covers(a, s2) && s2 != e1 || self.covers(b, s1) && s1 != e2 {
+            return None;
+        }
+        Some([(e1 == s2, s2), (e2 == s1, s1)].into_iter().map(|(meet, p)| meet.then(|| p.clone())).collect())
+    }
+
+    /// The point at `angle` on the circle around `center`, exactly on it.
+    fn on_circle(&mut self, center: &ExactPoint, radius2: &Number, angle: f32) -> Option<ExactPoint> {
+        // The rational parametrization by tan(angle / 2) keeps the point exactly on the circle.
+        let radius = self.sqrt(radius2)?;
+        let tan = (angle / 2.0).tan();
+        let (cos, sin) = match tan.is_finite() && tan.abs() < 1e6 {
+            true => {
+                let t = Number::from_f32(tan);
+                let t2 = self.mul(&t, &t);
+                let one = Number::from_int(1);
+                let denom = self.add(&one, &t2);
+                (self.div(&self.sub(&one, &t2), &denom)?, self.div(&self.add(&t, &t), &denom)?)
+            },
+            false => (Number::from_int(-1), Number::zero()),
+        };
+        let direction = ExactPoint { x: cos, y: sin };
+        Some(self.vmove(center, &direction, &radius))
+    }
+
+    /// Computes all common points of two graphs, exactly.
+    pub fn intersect(&mut self, a: &ExactGraph, b: &ExactGraph) -> ExactIntersection {
+        match self.slots(a, b) {
+            None => ExactIntersection::Coincident,
+            Some(slots) => {
+                let points: Vec<_> = slots.into_iter().flatten().collect();
+                match points.is_empty() {
+                    true => ExactIntersection::None,
+                    false => ExactIntersection::Points(points),
+                }
+            },
+        }
+    }
+
+    /// The common points of two graphs in their slots, exactly, as given by
+    /// [`slots`](super::intersection::slots). `None` when the graphs coincide.
+    pub fn slots(&mut self, a: &ExactGraph, b: &ExactGraph) -> Option<Vec<Option<ExactPoint>>> {
+        use ExactGraph::*;
+
+        match (a, b) {
+            (
+                Line { from: f1, to: t1 } | Ray { from: f1, to: t1 } | Segment { from: f1, to: t1 },
+                Line { from: f2, to: t2 } | Ray { from: f2, to: t2 } | Segment { from: f2, to: t2 },
+            ) => {
+                let (d1, d2) = (self.vsub(t1, f1), self.vsub(t2, f2));
+                let denom = self.cross(&d1, &d2);
+                if denom.is_zero() {
+                    return match self.contains(&a.carrier(), f2) {
+                        true => self.overlap(a, b),
+                        false => Some(Vec::new()),
+                    };
+                }
+                let t = self.div(&self.cross(&self.vsub(f2, f1), &d2), &denom).unwrap();
+                self.within(vec![self.vmove(f1, &d1, &t)], a, b)
+            },
+            (
+                Line { from, to } | Ray { from, to } | Segment { from, to },
+                Circle { center, radius2 } | Arc { center, radius2, .. },
+            )
+            | (
+                Circle { center, radius2 } | Arc { center, radius2, .. },
+                Line { from, to } | Ray { from, to } | Segment { from, to },
+            ) => {
+                // |from + d t - center|^2 = r^2, a quadratic in t with a positive leading term.
+                let (d, w) = (self.vsub(to, from), self.vsub(from, center));
+                let qa = self.dot(&d, &d);
+                let qb = self.dot(&d, &w);
+                let qc = self.sub(&self.dot(&w, &w), radius2);
+                let disc = self.sub(&self.mul(&qb, &qb), &self.mul(&qa, &qc));
+                let Some(root) = self.sqrt(&disc) else { return Some(Vec::new()) };
+                let roots = match root.is_zero() {
+                    true => vec![-&qb],
+                    false => vec![self.sub(&-&qb, &root), self.add(&-&qb, &root)],
+                };
+                let points = roots.iter().map(|t| self.vmove(from, &d, &self.div(t, &qa).unwrap())).collect();
+                self.within(points, a, b)
+            },
+            (
+                Circle { center: c1, radius2: r1 } | Arc { center: c1, radius2: r1, .. },
+                Circle { center: c2, radius2: r2 } | Arc { center: c2, radius2: r2, .. },
+            ) => {
+                let v = self.vsub(c2, c1);
+                let dist2 = self.dot(&v, &v);
+                if dist2.is_zero() {
+                    return match r1 == r2 {
+                        true => self.arc_overlap(a, b),
+                        false => Some(Vec::new()),
+                    };
+                }
+                // The foot on the line of centers as a fraction of it, and the offset from it
+                // as a fraction of the normal, counterclockwise first.
+                let along = self.div(&self.add(&dist2, &self.sub(r1, r2)), &self.add(&dist2, &dist2)).unwrap();
+                let offset2 = self.sub(&self.div(r1, &dist2).unwrap(), &self.mul(&along, &along));
+                let Some(offset) = self.sqrt(&offset2) else { return Some(Vec::new()) };
+                let foot = self.vmove(c1, &v, &along);
+                let normal = ExactPoint { x: -&v.y, y: v.x.clone() };
+                let points = match offset.is_zero() {
+                    true => vec![foot],
+                    false => vec![self.vmove(&foot, &normal, &offset), self.vmove(&foot, &normal, &-&offset)],
+                };
+                self.within(points, a, b)
+            },
+            (Point(p), Point(q)) => match p == q {
+                true => None,
+                false => Some(Vec::new()),
+            },
+            (Point(p), other) | (other, Point(p)) => Some(vec![self.contains(other, p).then(|| p.clone())]),
+        }
+    }
+
+    /// Computes the graph of `definition` exactly from the graphs of its dependencies.
+    pub fn evaluate<F>(&mut self, definition: &Definition, graph_of: F) -> Option<ExactGraph>
+    where
+        F: Fn(ObjectId) -> Option<ExactGraph>
+    {
+        let point_of = |id| match graph_of(id) {
+            Some(ExactGraph::Point(p)) => Some(p),
+            _ => None,
+        };
+
+        match definition {
+            Definition::FreePoint(pos) => Some(ExactGraph::Point(ExactPoint::from_world(*pos))),
+            Definition::PointOn { object, param } => match graph_of(*object)? {
+                ExactGraph::Point(p) => Some(ExactGraph::Point(p)),
+                ExactGraph::Circle { center, radius2 } => self.on_circle(&center, &radius2, *param).map(ExactGraph::Point),
+                ExactGraph::Arc { center, radius2, start, end } => {
+                    // Held within the arc as the float point is, at an end exactly.
+                    let angle = |p: &ExactPoint| {
+                        let v = self.vsub(p, &center);
+                        self.to_f64(&v.y).atan2(self.to_f64(&v.x)) as f32
+                    };
+                    let (from, to) = (angle(&start), angle(&end));
+                    let to = from + (to - from).rem_euclid(std::f32::consts::TAU);
+                    match clamp_to_arc(*param, from, to) {
+                        clamped if clamped == *param => self.on_circle(&center, &radius2, *param).map(ExactGraph::Point),
+                        clamped if clamped == from => Some(ExactGraph::Point(start)),
+                        _ => Some(ExactGraph::Point(end)),
+                    }
+                },
+                line => {
+                    let (from, to) = line.line_points()?;
+                    let param = match line {
+                        ExactGraph::Ray { .. } => param.max(0.0),
+                        ExactGraph::Segment { .. } => param.clamp(0.0, 1.0),
+                        _ => *param,
+                    };
+                    Some(ExactGraph::Point(self.vmove(from, &self.vsub(to, from), &Number::from_f32(param))))
+                },
+            },
+            Definition::Intersection { a, b, index } => {
+                let (a, b) = (graph_of(*a)?, graph_of(*b)?);
+                self.slots(&a, &b)?.get(*index).cloned().flatten().map(ExactGraph::Point)
+            },
+            Definition::Line { from, to } | Definition::Ray { from, to } | Definition::Segment { from, to } => {
+                let (from, to) = (point_of(*from)?, point_of(*to)?);
+                (from != to).then_some(match definition {
+                    Definition::Ray { .. } => ExactGraph::Ray { from, to },
+                    Definition::Segment { .. } => ExactGraph::Segment { from, to },
+                    _ => ExactGraph::Line { from, to },
+                })
+            },
+            Definition::Circle { center, through } => {
+                let (center, through) = (point_of(*center)?, point_of(*through)?);
+                let v = self.vsub(&through, &center);
+                let radius2 = self.dot(&v, &v);
+                (!radius2.is_zero()).then_some(ExactGraph::Circle { center, radius2 })
+            },
+            Definition::CircleRadius { center, from, to } => {
+                let center = point_of(*center)?;
+                let v = self.vsub(&point_of(*to)?, &point_of(*from)?);
+                let radius2 = self.dot(&v, &v);
+                (!radius2.is_zero()).then_some(ExactGraph::Circle { center, radius2 })
+            },
+            Definition::Arc { center, from, to, .. } => {
+                let (center, start, to) = (point_of(*center)?, point_of(*from)?, point_of(*to)?);
+                let (u, w) = (self.vsub(&start, &center), self.vsub(&to, &center));
+                let (radius2, distance2) = (self.dot(&u, &u), self.dot(&w, &w));
+                // The end is where the direction of `to` meets the circle.
+                let scale = self.sqrt(&self.div(&radius2, &distance2)?)?;
+                let end = self.vmove(&center, &w, &scale);
+                (!radius2.is_zero() && start != end).then_some(ExactGraph::Arc { center, radius2, start, end })
+            },
+            // Their equations are cubic, beyond square roots.
+            Definition::Neusis { .. } | Definition::Fold(_) => None,
+        }
+    }
+}
+
+/// The graphs of a construction kept in constructible numbers, for its exact mode.
+#[derive(Debug, Clone, Default)]
+pub struct Exact {
+    pub tower: Tower,
+    /// `None` for an object undefined in its configuration. The objects beyond square roots,
+    /// and those depending on them, are left out.
+    graphs: HashMap<ObjectId, Option<ExactGraph>>,
+}
+
+impl Exact {
+    /// Starts over with a new tower, so that it doesn't keep the roots of old configurations.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn graph(&self, id: ObjectId) -> Option<&ExactGraph> {
+        self.graphs.get(&id)?.as_ref()
+    }
+
+    /// The graph of `definition` from the graphs kept, `None` if it is beyond square roots.
+    pub fn evaluate(&mut self, definition: &Definition) -> Option<Option<ExactGraph>> {
+        let beyond = matches!(definition, Definition::Neusis { .. } | Definition::Fold(_));
+        if beyond || definition.dependencies().iter().any(|id| !self.graphs.contains_key(id)) {
+            return None;
+        }
+        let graphs = &self.graphs;
+        Some(self.tower.evaluate(definition, |id| graphs.get(&id).cloned().flatten()))
+    }
+
+    /// Evaluates and keeps the graph of `id`. `None` if it is beyond square roots.
+    pub fn update(&mut self, id: ObjectId, definition: &Definition) -> Option<Option<ExactGraph>> {
+        let graph = self.evaluate(definition);
+        match &graph {
+            Some(graph) => self.graphs.insert(id, graph.clone()),
+            None => self.graphs.remove(&id),
+        };
+        graph
+    }
+}
+
+/// Evaluates the objects of a construction exactly, on demand. Exact numbers grow with
+/// every independent square root, so only the objects asked for and their dependencies
+/// are computed.
+pub struct Evaluation<'a> {
+    construction: &'a Construction,
+    graphs: HashMap<ObjectId, Option<ExactGraph>>,
+}
+
+impl<'a> Evaluation<'a> {
+    pub fn new(construction: &'a Construction) -> Self {
+        Self { construction, graphs: HashMap::new() }
+    }
+
+    pub fn graph(&mut self, id: ObjectId, tower: &mut Tower) -> Option<ExactGraph> {
+        if let Some(graph) = self.graphs.get(&id) {
+            return graph.clone();
+        }
+
+        let definition = &self.construction.get(id)?.definition;
+        for dependency in definition.dependencies() {
+            self.graph(dependency, tower);
+        }
+        let graphs = &self.graphs;
+        let graph = tower.evaluate(definition, |id| graphs.get(&id).cloned().flatten());
+        self.graphs.insert(id, graph.clone());
+        graph
+    }
+}
+
+#[cfg(test)]
+mod test_exact {
+    use super::*;
+
+    fn int(value: i64) -> Number {
+        Number::from_int(value)
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut tower = Tower::new();
+        let two = tower.sqrt(&int(2)).unwrap();
+        assert_eq!(tower.mul(&two, &two), int(2));
+        assert_eq!(tower.sqrt(&int(4)), Some(int(2)));
+        assert_eq!(tower.sqrt(&int(-1)), None);
+
+        // Roots already in the tower don't extend it.
+        let eight = tower.sqrt(&int(8)).unwrap();
+        assert_eq!(eight, tower.mul(&int(2), &two));
+        let three = tower.sqrt(&int(3)).unwrap();
+        let six = tower.sqrt(&int(6)).unwrap();
+        assert_eq!(six, tower.mul(&two, &three));
+        assert_eq!(tower.depth(), 2);
+
+        // Nested roots are simplified: sqrt(3 + 2 sqrt(2)) = 1 + sqrt(2).
+        let nested = tower.sqrt(&tower.add(&int(3), &tower.mul(&int(2), &two))).unwrap();
+        assert_eq!(nested, tower.add(&int(1), &two));
+
+        let x = tower.add(&two, &three);
+        let inv = tower.inv(&x).unwrap();
+        assert_eq!(tower.mul(&x, &inv), int(1));
+        assert_eq!(tower.inv(&tower.sub(&x, &x)), None);
+
+        assert_eq!(tower.signum(&tower.sub(&two, &Number::from_f32(1.414))), Ordering::Greater);
+        assert_eq!(tower.signum(&tower.sub(&two, &Number::from_f32(1.415))), Ordering::Less);
+        assert_eq!(tower.cmp(&three, &two), Ordering::Greater);
+        assert!((tower.to_f64(&six) - 6f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_geometry() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(-1.5, 0.25)));
+        let b = c.add(Definition::FreePoint(p(3.0, 1.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+        let bisector = c.add(Definition::Line { from: top, to: bottom });
+        let ab = c.add(Definition::Line { from: a, to: b });
+        let mid = c.add(Definition::Intersection { a: bisector, b: ab, index: 0 });
+        let on_ca = c.add(Definition::Intersection { a: ab, b: ca, index: 1 });
+        c.update();
+
+        let mut tower = Tower::new();
+        let mut evaluation = Evaluation::new(&c);
+
+        // The midpoint is exact even though the bisector goes through square roots.
+        let Some(ExactGraph::Point(m)) = evaluation.graph(mid, &mut tower) else { panic!("Expected point") };
+        assert_eq!(m, ExactPoint::from_world(p(0.75, 0.625)));
+
+        // Same order as the float intersections.
+        for id in [top, bottom, on_ca] {
+            let Some(ExactGraph::Point(exact)) = evaluation.graph(id, &mut tower) else { panic!("Expected point") };
+            let Some(GraphType::Point(float)) = c.graph(id) else { panic!("Expected point") };
+            assert!((exact.to_world(&tower) - float).length() < 1e-4);
+        }
+
+        // Lines through other points of them are the same.
+        let (Some(line), Some(ExactGraph::Point(top))) = (evaluation.graph(bisector, &mut tower), evaluation.graph(top, &mut tower)) else {
+            panic!("Expected line")
+        };
+        assert!(tower.same(&line, &ExactGraph::Line { from: m.clone(), to: top }));
+        assert!(!tower.same(&line, &ExactGraph::Line { from: m, to: ExactPoint::from_world(p(0.0, 0.0)) }));
+    }
+
+    #[test]
+    fn test_bounded() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(-1.5, 0.25)));
+        let b = c.add(Definition::FreePoint(p(3.0, 1.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let segment = c.add(Definition::Segment { from: a, to: b });
+        let ray = c.add(Definition::Ray { from: b, to: a });
+        // The segment keeps the slots of its line, where the end comes second.
+        let on_segment = c.add(Definition::Intersection { a: segment, b: ca, index: 1 });
+        let on_ray = c.add(Definition::Intersection { a: ray, b: ca, index: 0 });
+        let off = c.add(Definition::Intersection { a: segment, b: ca, index: 0 });
+        c.update();
+
+        let mut tower = Tower::new();
+        let mut evaluation = Evaluation::new(&c);
+
+        // Same points kept as the float intersections.
+        for id in [on_segment, on_ray] {
+            let Some(ExactGraph::Point(exact)) = evaluation.graph(id, &mut tower) else { panic!("Expected point") };
+            let Some(GraphType::Point(float)) = c.graph(id) else { panic!("Expected point") };
+            assert!((exact.to_world(&tower) - float).length() < 1e-4);
+        }
+        assert_eq!(c.graph(off), None);
+        assert_eq!(evaluation.graph(off, &mut tower), None);
+        let end = ExactPoint::from_world(p(3.0, 1.0));
+        assert_eq!(evaluation.graph(on_segment, &mut tower), Some(ExactGraph::Point(end.clone())));
+
+        // The ray covers the segment, but not what lies beyond its start.
+        let (Some(segment), Some(ray)) = (evaluation.graph(segment, &mut tower), evaluation.graph(ray, &mut tower)) else {
+            panic!("Expected lines")
+        };
+        let beyond = ExactGraph::Segment { from: ExactPoint::from_world(p(7.5, 1.75)), to: ExactPoint::from_world(p(12.0, 2.5)) };
+        assert!(tower.same(&segment, &ray));
+        assert!(!tower.same(&ray, &beyond));
+        let touching = ExactGraph::Segment { from: end.clone(), to: ExactPoint::from_world(p(7.5, 1.75)) };
+        assert_eq!(tower.intersect(&segment, &touching), ExactIntersection::Points(vec![end]));
+    }
+
+    #[test]
+    fn test_arc() {
+        let mut c = Construction::new();
+        let o = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(-6.0, 8.0)));
+        let arc = c.add(Definition::Arc { center: o, from: a, to: b, region: ArcRegion::Sector });
+        let d = c.add(Definition::FreePoint(p(0.0, 3.0)));
+        let e = c.add(Definition::FreePoint(p(1.0, 3.0)));
+        let line = c.add(Definition::Line { from: d, to: e });
+        // The point of the line off the arc comes first.
+        let x = c.add(Definition::Intersection { a: line, b: arc, index: 1 });
+        let y = c.add(Definition::Intersection { a: line, b: arc, index: 0 });
+        let on = c.add(Definition::PointOn { object: arc, param: std::f32::consts::PI });
+        c.update();
+
+        let mut tower = Tower::new();
+        let mut evaluation = Evaluation::new(&c);
+
+        // The end lies on the circle, in the direction of the last point.
+        let end = ExactPoint::from_world(p(-3.0, 4.0));
+        let Some(ExactGraph::Arc { end: exact_end, .. }) = evaluation.graph(arc, &mut tower) else { panic!("Expected arc") };
+        assert_eq!(exact_end, end);
+        assert_eq!(evaluation.graph(x, &mut tower), Some(ExactGraph::Point(ExactPoint::from_world(p(4.0, 3.0)))));
+        let Some(GraphType::Point(float)) = c.graph(x) else { panic!("Expected point") };
+        assert!((float - p(4.0, 3.0)).length() < 1e-4);
+        assert_eq!(evaluation.graph(y, &mut tower), None);
+        assert_eq!(c.graph(y), None);
+        assert_eq!(evaluation.graph(on, &mut tower), Some(ExactGraph::Point(end)));
+
+        // Arcs meeting at their ends fill the same slots as the float ones.
+        let q = |x: f32, y: f32| ExactPoint::from_world(p(x, y));
+        let arc = |start, end| ExactGraph::Arc { center: q(0.0, 0.0), radius2: int(25), start, end };
+        let upper = arc(q(5.0, 0.0), q(-5.0, 0.0));
+        assert_eq!(tower.slots(&upper, &arc(q(-5.0, 0.0), q(0.0, -5.0))), Some(vec![Some(q(-5.0, 0.0)), None]));
+        assert_eq!(tower.slots(&upper, &arc(q(0.0, -5.0), q(5.0, 0.0))), Some(vec![None, Some(q(5.0, 0.0))]));
+        assert_eq!(tower.slots(&upper, &arc(q(0.0, 5.0), q(0.0, -5.0))), None);
+    }
+}
@@ -0,0 +1,236 @@
+use super::prelude::*;
+use super::intersection::{ Vec2, to_f64, to_world, sub, dot, cross, length, line_frame, intersect };
+use super::roots;
+
+/// The Huzita–Hatori axioms of origami, but the first: the fold through two points
+/// is the line of the straightedge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axiom {
+    /// Places a point onto another one.
+    PointToPoint,
+    /// Places a line onto another one.
+    LineToLine,
+    /// Folds through a point, perpendicular to a line.
+    Perpendicular,
+    /// Folds through a point, placing another one onto a line.
+    PointToLine,
+    /// Places two points onto two lines at once. Solves cubic equations.
+    TwoPointsToLines,
+    /// Places a point onto a line, perpendicular to another line.
+    PointToLinePerpendicular,
+}
+
+impl Axiom {
+    pub const ALL: [Axiom; 6] = [
+        Axiom::PointToPoint,
+        Axiom::LineToLine,
+        Axiom::Perpendicular,
+        Axiom::PointToLine,
+        Axiom::TwoPointsToLines,
+        Axiom::PointToLinePerpendicular,
+    ];
+
+    /// The number of the axiom, from 2 to 7.
+    pub fn number(&self) -> usize {
+        Self::ALL.iter().position(|axiom| axiom == self).unwrap() + 2
+    }
+
+    pub fn from_number(number: usize) -> Option<Self> {
+        Self::ALL.get(number.checked_sub(2)?).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PointToPoint => "Fold point onto point",
+            Self::LineToLine => "Fold line onto line",
+            Self::Perpendicular => "Fold perpendicular",
+            Self::PointToLine => "Fold point onto line",
+            Self::TwoPointsToLines => "Fold two points onto lines",
+            Self::PointToLinePerpendicular => "Fold point onto line, perpendicular",
+        }
+    }
+
+    pub fn inputs(&self) -> &'static [ObjectKind] {
+        use ObjectKind::{ Point, Line };
+        match self {
+            Self::PointToPoint => &[Point, Point],
+            Self::LineToLine => &[Line, Line],
+            Self::Perpendicular => &[Point, Line],
+            Self::PointToLine => &[Point, Point, Line],
+            Self::TwoPointsToLines => &[Point, Line, Point, Line],
+            Self::PointToLinePerpendicular => &[Point, Line, Line],
+        }
+    }
+}
+
+/// A crease, the `index`-th of the folds satisfying `axiom` for `inputs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fold {
+    pub axiom: Axiom,
+    pub inputs: Vec<ObjectId>,
+    pub index: usize,
+}
+
+impl Fold {
+    pub fn map_ids<F>(&self, map: F) -> Self
+    where
+        F: Fn(ObjectId) -> ObjectId
+    {
+        Self { axiom: self.axiom, inputs: self.inputs.iter().map(|id| map(*id)).collect(), index: self.index }
+    }
+
+    pub fn evaluate<F>(&self, graph_of: F, tolerance: &Tolerance) -> Option<GraphType>
+    where
+        F: Fn(ObjectId) -> Option<GraphType>
+    {
+        let graphs = self.inputs.iter().map(|id| graph_of(*id)).collect::<Option<Vec<_>>>()?;
+        folds(self.axiom, &graphs, tolerance).get(self.index).copied()
+    }
+}
+
+fn line(point: Vec2, dir: Vec2) -> GraphType {
+    let len = length(dir);
+    GraphType::Line { from: to_world(point), to: to_world([point[0] + dir[0] / len, point[1] + dir[1] / len]) }
+}
+
+fn perpendicular(v: Vec2) -> Vec2 {
+    [-v[1], v[0]]
+}
+
+/// The fold placing `a` onto `b`.
+fn bisector(a: Vec2, b: Vec2, tolerance: &Tolerance) -> Option<GraphType> {
+    let v = sub(b, a);
+    (length(v) > tolerance.distance as f64).then(|| line([(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0], perpendicular(v)))
+}
+
+/// All the folds for the graphs of the inputs, in a stable order.
+pub fn folds(axiom: Axiom, graphs: &[GraphType], tolerance: &Tolerance) -> Vec<GraphType> {
+    let point = |i: usize| match graphs.get(i) {
+        Some(GraphType::Point(pos)) => Some(to_f64(*pos)),
+        _ => None,
+    };
+    // Creases are made against the whole line of a ray or a segment.
+    let frame = |i: usize| graphs.get(i)?.line_points().and_then(|(from, to)| line_frame(from, to));
+    let angle = tolerance.angle as f64;
+
+    let result = || -> Option<Vec<GraphType>> {
+        Some(match axiom {
+            Axiom::PointToPoint => bisector(point(0)?, point(1)?, tolerance).into_iter().collect(),
+            Axiom::LineToLine => {
+                let ((o1, d1), (o2, d2)) = (frame(0)?, frame(1)?);
+                let denom = cross(d1, d2);
+                if denom.abs() <= angle {
+                    // The midline of parallel lines. Coincident lines fold anywhere.
+                    let offset = cross(d2, sub(o1, o2));
+                    if offset.abs() <= tolerance.distance as f64 {
+                        return None;
+                    }
+                    let n = perpendicular(d2);
+                    vec![line([o1[0] - n[0] * offset / 2.0, o1[1] - n[1] * offset / 2.0], d1)]
+                } else {
+                    let t = cross(sub(o2, o1), d2) / denom;
+                    let x = [o1[0] + d1[0] * t, o1[1] + d1[1] * t];
+                    vec![line(x, [d1[0] + d2[0], d1[1] + d2[1]]), line(x, [d1[0] - d2[0], d1[1] - d2[1]])]
+                }
+            },
+            Axiom::Perpendicular => vec![line(point(0)?, perpendicular(frame(1)?.1))],
+            Axiom::PointToLine => {
+                let (through, p) = (point(0)?, point(1)?);
+                let radius = length(sub(p, through)) as f32;
+                if radius <= tolerance.distance {
+                    return None;
+                }
+                let circle = GraphType::Circle { center: to_world(through), radius };
+                intersect(&circle, &graphs.get(2)?.carrier(), tolerance).points().iter()
+                    .filter_map(|image| bisector(p, to_f64(*image), tolerance))
+                    .collect()
+            },
+            Axiom::TwoPointsToLines => {
+                let (p1, (a1, u1), p2, (a2, u2)) = (point(0)?, frame(1)?, point(2)?, frame(3)?);
+                // The image of `p1` is `a1 + s u1`. The image of `p2` is on the second line where
+                // this cubic in `s` vanishes.
+                let image = |s: f64| [a1[0] + u1[0] * s, a1[1] + u1[1] * s];
+                let h = |s: f64| {
+                    let q = image(s);
+                    let w = sub(q, p1);
+                    let m = [(p1[0] + q[0]) / 2.0, (p1[1] + q[1]) / 2.0];
+                    cross(u2, sub(p2, a2)) * dot(w, w) - 2.0 * dot(sub(p2, m), w) * cross(u2, w)
+                };
+                // The coefficients from four samples, at a scale keeping them comparable.
+                let scale = 1.0 + length(sub(p1, a1)) + length(sub(p2, a2));
+                let (fm, f0, f1, f2) = (h(-scale), h(0.0), h(scale), h(2.0 * scale));
+                let b = (f1 + fm) / 2.0 - f0;
+                let e = (f1 - fm) / 2.0;
+                let a = (f2 - f0 - 4.0 * b - 2.0 * e) / 6.0;
+                roots::cubic(a, b, e - a, f0).into_iter()
+                    .filter_map(|t| bisector(p1, image(t * scale), tolerance))
+                    .collect()
+            },
+            Axiom::PointToLinePerpendicular => {
+                let (p, (a1, u1), (_, u2)) = (point(0)?, frame(1)?, frame(2)?);
+                let denom = cross(u1, u2);
+                if denom.abs() <= angle {
+                    return None;
+                }
+                let s = -cross(u1, sub(p, a1)) / denom;
+                vec![line([p[0] + u2[0] * s / 2.0, p[1] + u2[1] * s / 2.0], perpendicular(u2))]
+            },
+        })
+    };
+    result().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test_fold {
+    use super::*;
+    use super::super::intersection::distance_to;
+
+    fn reflect(fold: &GraphType, point: WorldPoint) -> WorldPoint {
+        let GraphType::Line { from, to } = *fold else { panic!("Expected line, got {:?}", fold) };
+        let d = (to - from).normalize();
+        let v = point - from;
+        from + d * v.dot(d) * 2.0 - v
+    }
+
+    #[test]
+    fn test_axioms() {
+        let tol = Tolerance::default();
+        let x_axis = GraphType::Line { from: p(0.0, 0.0), to: p(1.0, 0.0) };
+        let y_axis = GraphType::Line { from: p(0.0, 0.0), to: p(0.0, 1.0) };
+
+        let folds_of = |axiom, graphs: &[GraphType]| folds(axiom, graphs, &tol);
+        let fold = folds_of(Axiom::PointToPoint, &[GraphType::Point(p(1.0, 1.0)), GraphType::Point(p(3.0, 1.0))]);
+        assert!((reflect(&fold[0], p(1.0, 1.0)) - p(3.0, 1.0)).length() < 1e-4);
+
+        let folds = folds_of(Axiom::LineToLine, &[x_axis, y_axis]);
+        assert_eq!(folds.len(), 2);
+        assert!(folds.iter().all(|fold| (reflect(fold, p(2.0, 0.0)).x).abs() < 1e-4));
+
+        let folds = folds_of(Axiom::PointToLine, &[GraphType::Point(p(0.0, 3.0)), GraphType::Point(p(0.0, 8.0)), x_axis]);
+        assert_eq!(folds.len(), 2);
+        for fold in &folds {
+            assert!(distance_to(fold, p(0.0, 3.0)) < 1e-4);
+            assert!(reflect(fold, p(0.0, 8.0)).y.abs() < 1e-4);
+        }
+
+        let fold = folds_of(Axiom::PointToLinePerpendicular, &[GraphType::Point(p(1.0, 2.0)), y_axis, x_axis]);
+        assert!(reflect(&fold[0], p(1.0, 2.0)).x.abs() < 1e-4);
+        assert!((reflect(&fold[0], p(5.0, 7.0)).y - 7.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_cube_root() {
+        // Beloch's fold: (0, 1) onto y = -1 and (-2, 0) onto x = 2 makes a crease of slope 2^(1/3).
+        let folds = folds(Axiom::TwoPointsToLines, &[
+            GraphType::Point(p(0.0, 1.0)),
+            GraphType::Line { from: p(0.0, -1.0), to: p(1.0, -1.0) },
+            GraphType::Point(p(-2.0, 0.0)),
+            GraphType::Line { from: p(2.0, 0.0), to: p(2.0, 1.0) },
+        ], &Tolerance::default());
+        assert_eq!(folds.len(), 1);
+        let GraphType::Line { from, to } = folds[0] else { unreachable!() };
+        assert!(((to.y - from.y) / (to.x - from.x) - 2f32.cbrt()).abs() < 1e-4);
+        assert!((reflect(&folds[0], p(0.0, 1.0)).y + 1.0).abs() < 1e-3);
+        assert!((reflect(&folds[0], p(-2.0, 0.0)).x - 2.0).abs() < 1e-3);
+    }
+}
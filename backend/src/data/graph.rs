@@ -1,7 +1,174 @@
+use std::f32::consts::TAU;
+
 use super::prelude::*;
 
+/// What a filled arc closes its area with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcRegion {
+    /// The two radii to its ends, as a slice of the disc.
+    Sector,
+    /// The chord between its ends.
+    Segment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GraphType {
+    /// The infinite line through `from` and `to`.
     Line { from: WorldPoint, to: WorldPoint },
+    /// The half line starting at `from` and going through `to`.
+    Ray { from: WorldPoint, to: WorldPoint },
+    /// The part of the line between `from` and `to`.
+    Segment { from: WorldPoint, to: WorldPoint },
     Circle { center: WorldPoint, radius: f32 },
+    /// The part of the circle counterclockwise from the angle `start` to `end`, in radians,
+    /// with `start < end < start + TAU`.
+    Arc { center: WorldPoint, radius: f32, start: f32, end: f32, region: ArcRegion },
     Point(WorldPoint),
-}
\ No newline at end of file
+}
+
+impl GraphType {
+    /// Whether the graph is straight: a line, a ray or a segment.
+    pub fn is_line(&self) -> bool {
+        self.line_points().is_some()
+    }
+
+    /// Whether the graph is only part of its line or circle: a ray, a segment or an arc.
+    pub fn is_bounded(&self) -> bool {
+        matches!(self, Self::Ray { .. } | Self::Segment { .. } | Self::Arc { .. })
+    }
+
+    /// The two points a straight graph is drawn through.
+    pub fn line_points(&self) -> Option<(WorldPoint, WorldPoint)> {
+        match *self {
+            Self::Line { from, to } | Self::Ray { from, to } | Self::Segment { from, to } => Some((from, to)),
+            _ => None,
+        }
+    }
+
+    /// The part of its line a straight graph covers, as the range of the position along it,
+    /// 0 at `from` and 1 at `to`.
+    pub fn extent(&self) -> (f32, f32) {
+        match self {
+            Self::Ray { .. } => (0.0, f32::INFINITY),
+            Self::Segment { .. } => (0.0, 1.0),
+            _ => (f32::NEG_INFINITY, f32::INFINITY),
+        }
+    }
+
+    /// The whole line or circle of a bounded graph, or the graph itself.
+    pub fn carrier(&self) -> Self {
+        match *self {
+            Self::Arc { center, radius, .. } => Self::Circle { center, radius },
+            _ => match self.line_points() {
+                Some((from, to)) => Self::Line { from, to },
+                None => *self,
+            },
+        }
+    }
+
+    /// Whether the direction `angle` from the center falls within an arc. Always for a circle.
+    pub fn spans(&self, angle: f32) -> bool {
+        match *self {
+            Self::Arc { start, end, .. } => (angle - start).rem_euclid(TAU) <= end - start,
+            _ => true,
+        }
+    }
+
+    /// Clips a straight graph to `rect`. Returns `None` for the other graphs, or if it misses the rect.
+    pub fn clip(&self, rect: &WorldRect) -> Option<(WorldPoint, WorldPoint)> {
+        let (from, to) = self.line_points()?;
+        let (t_min, t_max) = self.extent();
+        clip_range(from, to, t_min, t_max, rect)
+    }
+}
+
+/// The point at `angle` on the circle around `center`.
+pub fn on_circle(center: WorldPoint, radius: f32, angle: f32) -> WorldPoint {
+    center + WorldVector::from_angle_and_length(super::euclid::Angle::radians(angle), radius)
+}
+
+/// Moves `angle` to the nearer end of the arc from `start` to `end` if it falls outside.
+pub fn clamp_to_arc(angle: f32, start: f32, end: f32) -> f32 {
+    let offset = (angle - start).rem_euclid(TAU);
+    if offset <= end - start {
+        angle
+    } else if offset - (end - start) < TAU - offset {
+        end
+    } else {
+        start
+    }
+}
+
+/// Clips the infinite line through `from` and `to` to `rect`.
+/// Returns `None` if the line misses the rect or `from` equals `to`.
+pub fn clip_line(from: WorldPoint, to: WorldPoint, rect: &WorldRect) -> Option<(WorldPoint, WorldPoint)> {
+    clip_range(from, to, f32::NEG_INFINITY, f32::INFINITY, rect)
+}
+
+/// Clips the part of the line through `from` and `to` between the positions `t_min` and `t_max` to `rect`.
+fn clip_range(from: WorldPoint, to: WorldPoint, mut t_min: f32, mut t_max: f32, rect: &WorldRect) -> Option<(WorldPoint, WorldPoint)> {
+    let dir = to - from;
+    if dir.square_length() == 0.0 {
+        return None;
+    }
+
+    for (p, d, min, max) in [
+        (from.x, dir.x, rect.min.x, rect.max.x),
+        (from.y, dir.y, rect.min.y, rect.max.y),
+    ] {
+        if d == 0.0 {
+            if p < min || p > max {
+                return None;
+            }
+        } else {
+            let (t0, t1) = ((min - p) / d, (max - p) / d);
+            t_min = t_min.max(t0.min(t1));
+            t_max = t_max.min(t0.max(t1));
+        }
+    }
+
+    if t_min > t_max {
+        None
+    } else {
+        Some((from + dir * t_min, from + dir * t_max))
+    }
+}
+
+#[cfg(test)]
+mod test_graph {
+    use super::*;
+
+    #[test]
+    fn test_clip_line() {
+        let rect = WorldRect::new(WorldPoint::new(-10.0, -10.0), WorldPoint::new(10.0, 10.0));
+
+        assert_eq!(
+            clip_line(WorldPoint::new(0.0, 0.0), WorldPoint::new(1.0, 0.0), &rect),
+            Some((WorldPoint::new(-10.0, 0.0), WorldPoint::new(10.0, 0.0)))
+        );
+        assert_eq!(
+            clip_line(WorldPoint::new(0.0, 0.0), WorldPoint::new(1.0, 1.0), &rect),
+            Some((WorldPoint::new(-10.0, -10.0), WorldPoint::new(10.0, 10.0)))
+        );
+        assert_eq!(clip_line(WorldPoint::new(0.0, 20.0), WorldPoint::new(1.0, 20.0), &rect), None);
+        assert_eq!(clip_line(WorldPoint::new(0.0, 30.0), WorldPoint::new(30.0, 0.0), &rect), None);
+        assert_eq!(clip_line(WorldPoint::new(1.0, 1.0), WorldPoint::new(1.0, 1.0), &rect), None);
+    }
+
+    #[test]
+    fn test_clip_bounded() {
+        let rect = WorldRect::new(WorldPoint::new(-10.0, -10.0), WorldPoint::new(10.0, 10.0));
+        let (from, to) = (WorldPoint::new(0.0, 0.0), WorldPoint::new(5.0, 0.0));
+
+        assert_eq!(GraphType::Ray { from, to }.clip(&rect), Some((from, WorldPoint::new(10.0, 0.0))));
+        assert_eq!(GraphType::Ray { from: to, to: from }.clip(&rect), Some((to, WorldPoint::new(-10.0, 0.0))));
+        assert_eq!(GraphType::Segment { from, to }.clip(&rect), Some((from, to)));
+        assert_eq!(
+            GraphType::Segment { from: WorldPoint::new(5.0, 5.0), to: WorldPoint::new(15.0, 5.0) }.clip(&rect),
+            Some((WorldPoint::new(5.0, 5.0), WorldPoint::new(10.0, 5.0)))
+        );
+        // Pointing away from the rect.
+        assert_eq!(GraphType::Ray { from: WorldPoint::new(20.0, 0.0), to: WorldPoint::new(30.0, 0.0) }.clip(&rect), None);
+        assert_eq!(GraphType::Circle { center: from, radius: 1.0 }.clip(&rect), None);
+    }
+}
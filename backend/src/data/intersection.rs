@@ -0,0 +1,486 @@
+use std::f32::consts::TAU;
+
+use super::prelude::*;
+use super::graph::on_circle;
+
+/// Thresholds used to decide the degenerate cases of an intersection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// Two distances closer than this are treated as equal. Decides tangency,
+    /// coincidence and whether a point lies on a curve.
+    pub distance: f32,
+    /// Two lines whose unit directions have a cross product below this are parallel.
+    pub angle: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self { distance: 1e-3, angle: 1e-6 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Intersection {
+    None,
+    /// The distinct common points. A tangency produces a single point.
+    Points(Vec<WorldPoint>),
+    /// The graphs are the same, or overlapping parts of a line, so they share infinitely many points.
+    Coincident,
+}
+
+impl Intersection {
+    pub fn points(&self) -> &[WorldPoint] {
+        match self {
+            Self::Points(points) => points,
+            _ => &[],
+        }
+    }
+}
+
+pub(super) type Vec2 = [f64; 2];
+
+pub(super) fn to_f64(p: WorldPoint) -> Vec2 {
+    [p.x as f64, p.y as f64]
+}
+
+pub(super) fn to_world(p: Vec2) -> WorldPoint {
+    WorldPoint::new(p[0] as f32, p[1] as f32)
+}
+
+pub(super) fn sub(a: Vec2, b: Vec2) -> Vec2 {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+pub(super) fn dot(a: Vec2, b: Vec2) -> f64 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+pub(super) fn cross(a: Vec2, b: Vec2) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+pub(super) fn length(a: Vec2) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// Returns the origin and unit direction of the line through `from` and `to`.
+pub(super) fn line_frame(from: WorldPoint, to: WorldPoint) -> Option<(Vec2, Vec2)> {
+    let (o, d) = (to_f64(from), sub(to_f64(to), to_f64(from)));
+    let len = length(d);
+    if len == 0.0 {
+        None
+    } else {
+        Some((o, [d[0] / len, d[1] / len]))
+    }
+}
+
+/// Computes all common points of two graphs.
+///
+/// Points are returned in a stable order so that an index can be used to name
+/// an intersection: along the direction of the line for line–circle pairs, and
+/// counterclockwise side of the `a` center to `b` center first for circle–circle pairs.
+/// Rays, segments and arcs only keep the points of their line or circle within them.
+pub fn intersect(a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Intersection {
+    match slots(a, b, tolerance) {
+        None => Intersection::Coincident,
+        Some(slots) => {
+            let points: Vec<_> = slots.into_iter().flatten().collect();
+            match points.is_empty() {
+                true => Intersection::None,
+                false => Intersection::Points(points),
+            }
+        },
+    }
+}
+
+/// The common points of two graphs, each in its slot among the points of their lines and
+/// circles, in the order of [`intersect`]. A point off a ray, segment or arc leaves its slot
+/// empty instead of shifting the others, so that an index keeps naming the same point as
+/// the graphs move. `None` when the graphs coincide.
+pub fn slots(a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Option<Vec<Option<WorldPoint>>> {
+    use GraphType::*;
+
+    let intersection = match (a, b) {
+        (
+            Line { from: f1, to: t1 } | Ray { from: f1, to: t1 } | Segment { from: f1, to: t1 },
+            Line { from: f2, to: t2 } | Ray { from: f2, to: t2 } | Segment { from: f2, to: t2 },
+        ) => match line_line(*f1, *t1, *f2, *t2, tolerance) {
+            Intersection::Coincident => return overlap(a, b, tolerance),
+            intersection => intersection,
+        },
+        (
+            Line { from, to } | Ray { from, to } | Segment { from, to },
+            Circle { center, radius } | Arc { center, radius, .. },
+        )
+        | (
+            Circle { center, radius } | Arc { center, radius, .. },
+            Line { from, to } | Ray { from, to } | Segment { from, to },
+        ) => line_circle(*from, *to, *center, *radius, tolerance),
+        (
+            Circle { center: c1, radius: r1 } | Arc { center: c1, radius: r1, .. },
+            Circle { center: c2, radius: r2 } | Arc { center: c2, radius: r2, .. },
+        ) => match circle_circle(*c1, *r1, *c2, *r2, tolerance) {
+            Intersection::Coincident => return arc_overlap(a, b, tolerance),
+            intersection => intersection,
+        },
+        (Point(p1), Point(p2)) => match (*p1 - *p2).length() <= tolerance.distance {
+            true => return None,
+            false => Intersection::None,
+        },
+        (Point(p), other) | (other, Point(p)) => match distance_to(other, *p) <= tolerance.distance {
+            true => Intersection::Points(vec![*p]),
+            false => Intersection::None,
+        },
+    };
+    Some(within(intersection, a, b, tolerance))
+}
+
+/// Distance from `point` to the nearest point of `graph`.
+pub fn distance_to(graph: &GraphType, point: WorldPoint) -> f32 {
+    let p = to_f64(point);
+    match graph {
+        GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to } => match line_frame(*from, *to) {
+            Some((o, d)) if graph.is_bounded() => {
+                // The nearest point of the line, moved back within the graph.
+                let (lo, hi) = graph.extent();
+                let len = (*to - *from).length() as f64;
+                let t = dot(sub(p, o), d).clamp(lo as f64 * len, hi as f64 * len);
+                length(sub(p, [o[0] + d[0] * t, o[1] + d[1] * t])) as f32
+            },
+            Some((o, d)) => cross(d, sub(p, o)).abs() as f32,
+            None => (point - *from).length(),
+        },
+        GraphType::Circle { center, radius } => (length(sub(p, to_f64(*center))) - *radius as f64).abs() as f32,
+        GraphType::Arc { center, radius, start, end, .. } => match graph.spans((point - *center).angle_from_x_axis().radians) {
+            true => (length(sub(p, to_f64(*center))) - *radius as f64).abs() as f32,
+            // The nearer end.
+            false => [*start, *end].iter()
+                .map(|angle| (point - on_circle(*center, *radius, *angle)).length())
+                .fold(f32::INFINITY, f32::min),
+        },
+        GraphType::Point(pos) => (point - *pos).length(),
+    }
+}
+
+/// Empties the slots of the points of `intersection`, found on the lines of `a` and `b`, which are off the graphs.
+fn within(intersection: Intersection, a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Vec<Option<WorldPoint>> {
+    intersection.points().iter()
+        .map(|p| [a, b].iter().all(|graph| !graph.is_bounded() || distance_to(graph, *p) <= tolerance.distance).then_some(*p))
+        .collect()
+}
+
+/// The common part of two straight graphs on the same line: the single end where they
+/// meet in one slot, or `None` when they share more.
+fn overlap(a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Option<Vec<Option<WorldPoint>>> {
+    let (Some((f1, t1)), Some((f2, t2))) = (a.line_points(), b.line_points()) else { return Some(Vec::new()) };
+    let Some((o, d)) = line_frame(f1, t1) else { return Some(Vec::new()) };
+    // The range each graph covers, as distances along `a` from its `from`.
+    let range = |graph: &GraphType, from: WorldPoint, to: WorldPoint| {
+        let (s0, s1) = (dot(sub(to_f64(from), o), d), dot(sub(to_f64(to), o), d));
+        let (lo, hi) = graph.extent();
+        let ends = [lo, hi].map(|t| s0 + (s1 - s0) * t as f64);
+        (ends[0].min(ends[1]), ends[0].max(ends[1]))
+    };
+    let ((lo1, hi1), (lo2, hi2)) = (range(a, f1, t1), range(b, f2, t2));
+    let (lo, hi) = (lo1.max(lo2), hi1.min(hi2));
+
+    let eps = tolerance.distance as f64;
+    if hi - lo < -eps {
+        Some(vec![None])
+    } else if hi - lo <= eps {
+        let t = (lo + hi) / 2.0;
+        Some(vec![Some(to_world([o[0] + d[0] * t, o[1] + d[1] * t]))])
+    } else {
+        None
+    }
+}
+
+/// The common part of two circles or arcs on the same circle: `None` when they share more
+/// than their ends. Otherwise the first slot holds the end of `a` where `b` starts, and
+/// the second the start of `a` where `b` ends, when they meet there.
+fn arc_overlap(a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Option<Vec<Option<WorldPoint>>> {
+    // The start and the sweep of each.
+    let range = |graph: &GraphType| match *graph {
+        GraphType::Arc { start, end, .. } => (start, end - start),
+        _ => (0.0, TAU),
+    };
+    let (GraphType::Circle { center, radius } | GraphType::Arc { center, radius, .. }) = *a else { return Some(Vec::new()) };
+    let ((s1, l1), (s2, l2)) = (range(a), range(b));
+    let eps = tolerance.distance / radius;
+    let (d1, d2) = ((s2 - s1).rem_euclid(TAU), (s1 - s2).rem_euclid(TAU));
+
+    // One starts within the other.
+    if d1 < l1 - eps || d2 < l2 - eps {
+        return None;
+    }
+    Some([(d1 - l1, s2), (d2 - l2, s1)].into_iter()
+        .map(|(gap, angle)| (gap.abs() <= eps).then(|| on_circle(center, radius, angle)))
+        .collect())
+}
+
+fn line_line(f1: WorldPoint, t1: WorldPoint, f2: WorldPoint, t2: WorldPoint, tolerance: &Tolerance) -> Intersection {
+    let (Some((o1, d1)), Some((o2, d2))) = (line_frame(f1, t1), line_frame(f2, t2)) else {
+        return Intersection::None;
+    };
+
+    let denom = cross(d1, d2);
+    if denom.abs() <= tolerance.angle as f64 {
+        return if cross(d1, sub(o2, o1)).abs() <= tolerance.distance as f64 {
+            Intersection::Coincident
+        } else {
+            Intersection::None
+        };
+    }
+
+    let t = cross(sub(o2, o1), d2) / denom;
+    Intersection::Points(vec![to_world([o1[0] + d1[0] * t, o1[1] + d1[1] * t])])
+}
+
+fn line_circle(from: WorldPoint, to: WorldPoint, center: WorldPoint, radius: f32, tolerance: &Tolerance) -> Intersection {
+    let Some((o, d)) = line_frame(from, to) else {
+        return Intersection::None;
+    };
+    let c = to_f64(center);
+    let r = radius as f64;
+
+    // Foot of the perpendicular from the center, as a parameter along the line.
+    let t = dot(sub(c, o), d);
+    let foot = [o[0] + d[0] * t, o[1] + d[1] * t];
+    let dist = length(sub(c, foot));
+
+    if (dist - r).abs() <= tolerance.distance as f64 {
+        Intersection::Points(vec![to_world(foot)])
+    } else if dist > r {
+        Intersection::None
+    } else {
+        let h = (r * r - dist * dist).sqrt();
+        Intersection::Points(vec![
+            to_world([foot[0] - d[0] * h, foot[1] - d[1] * h]),
+            to_world([foot[0] + d[0] * h, foot[1] + d[1] * h]),
+        ])
+    }
+}
+
+fn circle_circle(c1: WorldPoint, r1: f32, c2: WorldPoint, r2: f32, tolerance: &Tolerance) -> Intersection {
+    let (p1, p2) = (to_f64(c1), to_f64(c2));
+    let (r1, r2) = (r1 as f64, r2 as f64);
+    let eps = tolerance.distance as f64;
+    let v = sub(p2, p1);
+    let d = length(v);
+
+    if d <= eps {
+        return if (r1 - r2).abs() <= eps {
+            Intersection::Coincident
+        } else {
+            Intersection::None
+        };
+    }
+
+    let u = [v[0] / d, v[1] / d];
+    if (d - (r1 + r2)).abs() <= eps || (d - (r1 - r2).abs()).abs() <= eps {
+        // Tangency, the common point lies on the line of centers.
+        let a = if (d - (r1 + r2)).abs() <= eps || r1 >= r2 { r1 } else { -r1 };
+        return Intersection::Points(vec![to_world([p1[0] + u[0] * a, p1[1] + u[1] * a])]);
+    }
+    if d > r1 + r2 || d < (r1 - r2).abs() {
+        return Intersection::None;
+    }
+
+    let a = (d * d + r1 * r1 - r2 * r2) / (2.0 * d);
+    let h = (r1 * r1 - a * a).max(0.0).sqrt();
+    let base = [p1[0] + u[0] * a, p1[1] + u[1] * a];
+    let normal = [-u[1], u[0]];
+    Intersection::Points(vec![
+        to_world([base[0] + normal[0] * h, base[1] + normal[1] * h]),
+        to_world([base[0] - normal[0] * h, base[1] - normal[1] * h]),
+    ])
+}
+
+#[cfg(test)]
+mod test_intersection {
+    use super::*;
+
+    fn line(from: WorldPoint, to: WorldPoint) -> GraphType {
+        GraphType::Line { from, to }
+    }
+
+    fn circle(center: WorldPoint, radius: f32) -> GraphType {
+        GraphType::Circle { center, radius }
+    }
+
+    fn assert_points(result: Intersection, expected: &[WorldPoint]) {
+        let points = match result {
+            Intersection::Points(points) => points,
+            other => panic!("Expected points, got {:?}", other),
+        };
+        assert_eq!(points.len(), expected.len(), "{:?}", points);
+        points.iter().zip(expected).for_each(|(a, b)| {
+            assert!((*a - *b).length() < 1e-3, "{:?} != {:?}", a, b);
+        });
+    }
+
+    fn assert_slots(result: Option<Vec<Option<WorldPoint>>>, expected: &[Option<WorldPoint>]) {
+        let slots = result.expect("Expected slots");
+        assert_eq!(slots.len(), expected.len(), "{:?}", slots);
+        slots.iter().zip(expected).for_each(|(a, b)| match (a, b) {
+            (Some(a), Some(b)) => assert!((*a - *b).length() < 1e-3, "{:?} != {:?}", a, b),
+            (a, b) => assert_eq!(a, b),
+        });
+    }
+
+    #[test]
+    fn test_line_line() {
+        let tol = Tolerance::default();
+
+        assert_points(
+            intersect(&line(p(-1.0, 0.0), p(1.0, 0.0)), &line(p(3.0, -2.0), p(3.0, 5.0)), &tol),
+            &[p(3.0, 0.0)]
+        );
+        assert_points(
+            intersect(&line(p(0.0, 0.0), p(1.0, 1.0)), &line(p(0.0, 4.0), p(1.0, 3.0)), &tol),
+            &[p(2.0, 2.0)]
+        );
+        assert_eq!(
+            intersect(&line(p(0.0, 0.0), p(1.0, 1.0)), &line(p(0.0, 1.0), p(1.0, 2.0)), &tol),
+            Intersection::None
+        );
+        assert_eq!(
+            intersect(&line(p(0.0, 0.0), p(1.0, 1.0)), &line(p(5.0, 5.0), p(-3.0, -3.0)), &tol),
+            Intersection::Coincident
+        );
+        assert_eq!(
+            intersect(&line(p(1.0, 1.0), p(1.0, 1.0)), &line(p(0.0, 1.0), p(1.0, 2.0)), &tol),
+            Intersection::None
+        );
+    }
+
+    #[test]
+    fn test_line_circle() {
+        let tol = Tolerance::default();
+        let c = circle(p(0.0, 0.0), 5.0);
+
+        assert_points(intersect(&line(p(-10.0, 3.0), p(10.0, 3.0)), &c, &tol), &[p(-4.0, 3.0), p(4.0, 3.0)]);
+        // The order follows the direction of the line.
+        assert_points(intersect(&c, &line(p(10.0, 3.0), p(-10.0, 3.0)), &tol), &[p(4.0, 3.0), p(-4.0, 3.0)]);
+        assert_points(intersect(&line(p(5.0, -1.0), p(5.0, 1.0)), &c, &tol), &[p(5.0, 0.0)]);
+        assert_points(intersect(&line(p(5.0005, -1.0), p(5.0005, 1.0)), &c, &tol), &[p(5.0005, 0.0)]);
+        assert_eq!(intersect(&line(p(6.0, -1.0), p(6.0, 1.0)), &c, &tol), Intersection::None);
+        assert_points(intersect(&line(p(0.0, 0.0), p(1.0, 0.0)), &c, &tol), &[p(-5.0, 0.0), p(5.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_circle_circle() {
+        let tol = Tolerance::default();
+
+        assert_points(
+            intersect(&circle(p(0.0, 0.0), 5.0), &circle(p(6.0, 0.0), 5.0), &tol),
+            &[p(3.0, 4.0), p(3.0, -4.0)]
+        );
+        // Swapping the circles flips the order.
+        assert_points(
+            intersect(&circle(p(6.0, 0.0), 5.0), &circle(p(0.0, 0.0), 5.0), &tol),
+            &[p(3.0, -4.0), p(3.0, 4.0)]
+        );
+        // External and internal tangency.
+        assert_points(intersect(&circle(p(0.0, 0.0), 2.0), &circle(p(5.0, 0.0), 3.0), &tol), &[p(2.0, 0.0)]);
+        assert_points(intersect(&circle(p(0.0, 0.0), 5.0), &circle(p(2.0, 0.0), 3.0), &tol), &[p(5.0, 0.0)]);
+        assert_points(intersect(&circle(p(2.0, 0.0), 3.0), &circle(p(0.0, 0.0), 5.0), &tol), &[p(5.0, 0.0)]);
+        // Apart, nested, concentric and identical.
+        assert_eq!(intersect(&circle(p(0.0, 0.0), 1.0), &circle(p(5.0, 0.0), 1.0), &tol), Intersection::None);
+        assert_eq!(intersect(&circle(p(0.0, 0.0), 5.0), &circle(p(1.0, 0.0), 1.0), &tol), Intersection::None);
+        assert_eq!(intersect(&circle(p(0.0, 0.0), 5.0), &circle(p(0.0, 0.0), 3.0), &tol), Intersection::None);
+        assert_eq!(intersect(&circle(p(1.0, 1.0), 5.0), &circle(p(1.0, 1.0), 5.0), &tol), Intersection::Coincident);
+    }
+
+    #[test]
+    fn test_point() {
+        let tol = Tolerance::default();
+
+        assert_points(intersect(&GraphType::Point(p(3.0, 4.0)), &circle(p(0.0, 0.0), 5.0), &tol), &[p(3.0, 4.0)]);
+        assert_points(intersect(&line(p(0.0, 0.0), p(1.0, 1.0)), &GraphType::Point(p(2.0, 2.0)), &tol), &[p(2.0, 2.0)]);
+        assert_eq!(intersect(&GraphType::Point(p(3.0, 3.0)), &circle(p(0.0, 0.0), 5.0), &tol), Intersection::None);
+        assert_eq!(intersect(&GraphType::Point(p(1.0, 2.0)), &GraphType::Point(p(1.0, 2.0)), &tol), Intersection::Coincident);
+        assert_eq!(intersect(&GraphType::Point(p(1.0, 2.0)), &GraphType::Point(p(2.0, 2.0)), &tol), Intersection::None);
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let loose = Tolerance { distance: 0.1, ..Default::default() };
+
+        assert_eq!(intersect(&line(p(5.05, -1.0), p(5.05, 1.0)), &circle(p(0.0, 0.0), 5.0), &loose).points().len(), 1);
+        assert_eq!(intersect(&line(p(5.05, -1.0), p(5.05, 1.0)), &circle(p(0.0, 0.0), 5.0), &Tolerance::default()), Intersection::None);
+        assert_eq!(
+            intersect(&circle(p(0.05, 0.0), 5.0), &circle(p(0.0, 0.0), 5.0), &loose),
+            Intersection::Coincident
+        );
+    }
+
+    #[test]
+    fn test_bounded() {
+        let tol = Tolerance::default();
+        let segment = |from, to| GraphType::Segment { from, to };
+        let ray = |from, to| GraphType::Ray { from, to };
+        let c = circle(p(0.0, 0.0), 5.0);
+
+        // Points outside a segment or behind a ray are dropped, the order of the rest is kept.
+        assert_points(intersect(&segment(p(-10.0, 3.0), p(0.0, 3.0)), &c, &tol), &[p(-4.0, 3.0)]);
+        assert_points(intersect(&ray(p(0.0, 3.0), p(-1.0, 3.0)), &c, &tol), &[p(-4.0, 3.0)]);
+        assert_eq!(intersect(&segment(p(-3.0, 3.0), p(3.0, 3.0)), &c, &tol), Intersection::None);
+        assert_points(intersect(&segment(p(0.0, 0.0), p(2.0, 2.0)), &line(p(0.0, 2.0), p(2.0, 0.0)), &tol), &[p(1.0, 1.0)]);
+        assert_eq!(intersect(&segment(p(0.0, 0.0), p(2.0, 2.0)), &ray(p(0.0, 4.0), p(-1.0, 5.0)), &tol), Intersection::None);
+        // An end on the other graph still counts.
+        assert_points(intersect(&segment(p(0.0, 0.0), p(5.0, 0.0)), &c, &tol), &[p(5.0, 0.0)]);
+
+        // On the same line, they coincide only where they overlap.
+        let (a, b) = (p(0.0, 0.0), p(4.0, 0.0));
+        assert_eq!(intersect(&segment(a, b), &line(p(-1.0, 0.0), p(1.0, 0.0)), &tol), Intersection::Coincident);
+        assert_eq!(intersect(&segment(a, b), &segment(p(3.0, 0.0), p(8.0, 0.0)), &tol), Intersection::Coincident);
+        assert_points(intersect(&segment(a, b), &segment(p(8.0, 0.0), b), &tol), &[b]);
+        assert_eq!(intersect(&segment(a, b), &segment(p(5.0, 0.0), p(8.0, 0.0)), &tol), Intersection::None);
+        assert_eq!(intersect(&ray(a, b), &ray(p(-1.0, 0.0), p(-2.0, 0.0)), &tol), Intersection::None);
+        assert_points(intersect(&ray(a, b), &ray(a, p(-2.0, 0.0)), &tol), &[a]);
+        assert_eq!(intersect(&ray(a, b), &ray(p(9.0, 0.0), p(10.0, 0.0)), &tol), Intersection::Coincident);
+
+        assert!((distance_to(&segment(a, b), p(7.0, 4.0)) - 5.0).abs() < 1e-4);
+        assert!((distance_to(&ray(a, b), p(7.0, 4.0)) - 4.0).abs() < 1e-4);
+        assert!((distance_to(&ray(a, b), p(-3.0, 4.0)) - 5.0).abs() < 1e-4);
+
+        // The points off a bounded graph leave their slots of the whole line empty.
+        assert_slots(slots(&ray(p(0.0, 3.0), p(-1.0, 3.0)), &c, &tol), &[None, Some(p(-4.0, 3.0))]);
+        assert_slots(slots(&segment(p(-3.0, 3.0), p(3.0, 3.0)), &c, &tol), &[None, None]);
+        assert_slots(slots(&segment(a, b), &segment(p(8.0, 0.0), b), &tol), &[Some(b)]);
+        assert_slots(slots(&segment(a, b), &segment(p(5.0, 0.0), p(8.0, 0.0)), &tol), &[None]);
+        assert_eq!(slots(&segment(a, b), &line(p(-1.0, 0.0), p(1.0, 0.0)), &tol), None);
+    }
+
+    #[test]
+    fn test_arc() {
+        use std::f32::consts::PI;
+        let tol = Tolerance::default();
+        let arc = |start, end| GraphType::Arc { center: p(0.0, 0.0), radius: 5.0, start, end, region: ArcRegion::Sector };
+        let upper = arc(0.0, PI);
+
+        // Only the points within the angular range are kept.
+        let above = line(p(-10.0, 3.0), p(10.0, 3.0));
+        assert_eq!(intersect(&above, &upper, &tol), intersect(&above, &circle(p(0.0, 0.0), 5.0), &tol));
+        assert_eq!(intersect(&line(p(-10.0, -3.0), p(10.0, -3.0)), &upper, &tol), Intersection::None);
+        assert_points(intersect(&upper, &circle(p(5.0, 0.0), 5.0), &tol), &[p(2.5, 4.330127)]);
+
+        // On the same circle, they coincide only where they overlap.
+        assert_eq!(intersect(&upper, &arc(PI / 2.0, 1.5 * PI), &tol), Intersection::Coincident);
+        assert_eq!(intersect(&upper, &circle(p(0.0, 0.0), 5.0), &tol), Intersection::Coincident);
+        assert_points(intersect(&upper, &arc(PI, 2.0 * PI), &tol), &[p(-5.0, 0.0), p(5.0, 0.0)]);
+        assert_eq!(intersect(&arc(0.0, PI / 2.0), &arc(PI, 1.5 * PI), &tol), Intersection::None);
+
+        // The slots stay the same whichever ends meet.
+        assert_slots(slots(&upper, &arc(PI, 1.5 * PI), &tol), &[Some(p(-5.0, 0.0)), None]);
+        assert_slots(slots(&upper, &arc(-0.5 * PI, 0.0), &tol), &[None, Some(p(5.0, 0.0))]);
+        assert_eq!(slots(&upper, &arc(0.5 * PI, 1.5 * PI), &tol), None);
+        // A point off the arc leaves its slot empty.
+        assert_slots(slots(&line(p(-10.0, -3.0), p(10.0, -3.0)), &upper, &tol), &[None, None]);
+        assert_slots(slots(&upper, &circle(p(5.0, 0.0), 5.0), &tol), &[Some(p(2.5, 4.330127)), None]);
+
+        assert!((distance_to(&upper, p(0.0, 10.0)) - 5.0).abs() < 1e-4);
+        assert!((distance_to(&upper, p(0.0, -10.0)) - 125f32.sqrt()).abs() < 1e-4);
+    }
+}
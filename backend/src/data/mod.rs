@@ -1,13 +1,22 @@
 pub mod matrix;
 pub mod graph;
+pub mod intersection;
+pub mod construction;
 pub mod dirty_flag;
 pub mod color;
+pub mod style;
+pub mod exact;
+pub mod rules;
+pub mod roots;
+pub mod fold;
+pub mod neusis;
 
 use lyon::geom::euclid;
 
 pub mod prelude {
     pub use super::matrix::ProjectWorldToViewport;
-    pub use super::color;
+    pub use super::color::{ self, Rgba8 };
+    pub use super::style::Style;
 
     pub use lyon::path::Path;
 
@@ -19,12 +28,15 @@ pub mod prelude {
     pub type WorldPoint = super::euclid::Point2D<f32, WorldSpace>;
     pub type WorldSize = super::euclid::Size2D<f32, WorldSpace>;
     pub type WorldVector = super::euclid::Vector2D<f32, WorldSpace>;
+    pub type WorldRect = super::euclid::Box2D<f32, WorldSpace>;
     
     pub struct ViewportSpace;
     pub type ViewportPoint = super::euclid::Point2D<f32, ViewportSpace>;
     pub type ProjMatrix = super::euclid::Transform3D<f32, WorldSpace, ViewportSpace>;
 
-    pub use super::graph::GraphType;
+    pub use super::graph::{ GraphType, ArcRegion };
+    pub use super::intersection::{ Intersection, Tolerance };
+    pub use super::construction::{ Construction, Definition, ObjectId, ObjectKind };
 
     #[derive(Default)]
     pub struct ViewData {
@@ -32,6 +44,20 @@ pub mod prelude {
         pub size: ScreenSize,
         pub pixel_size: f32,
     }
+
+    impl ViewData {
+        /// The world area currently covered by the viewport.
+        pub fn world_rect(&self) -> WorldRect {
+            let half = WorldVector::new(self.size.width, self.size.height) * self.pixel_size / 2.0;
+            WorldRect::new(self.center - half, self.center + half)
+        }
+    }
     
     pub use super::dirty_flag::DirtyFlag;
+
+    /// Shorthand for the points of the tests.
+    #[cfg(test)]
+    pub fn p(x: f32, y: f32) -> WorldPoint {
+        WorldPoint::new(x, y)
+    }
 }
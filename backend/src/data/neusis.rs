@@ -0,0 +1,118 @@
+use std::f64::consts::PI;
+
+use super::prelude::*;
+use super::intersection::{ Vec2, to_f64, to_world, sub, dot, cross, length, line_frame };
+use super::roots;
+
+/// Number of directions sampled when looking for the solutions.
+const SAMPLES: usize = 720;
+
+/// The signed distances from `pole` along `u` to the points of `graph`.
+fn crossings(graph: &GraphType, pole: Vec2, u: Vec2) -> [Option<f64>; 2] {
+    match *graph {
+        GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to } => match line_frame(from, to) {
+            Some((o, v)) if cross(u, v).abs() > 1e-9 => {
+                let t = cross(sub(o, pole), v) / cross(u, v);
+                // The mark must be within a ray or a segment.
+                let (lo, hi) = graph.extent();
+                let along = dot(sub([pole[0] + u[0] * t, pole[1] + u[1] * t], o), v) / length(sub(to_f64(to), o));
+                [(lo as f64..=hi as f64).contains(&along).then_some(t), None]
+            },
+            _ => [None, None],
+        },
+        GraphType::Circle { center, radius } | GraphType::Arc { center, radius, .. } => {
+            let v = sub(pole, to_f64(center));
+            let k = dot(u, v);
+            let discriminant = k * k - dot(v, v) + (radius as f64).powi(2);
+            if discriminant < 0.0 {
+                return [None, None];
+            }
+            // The mark must be within an arc.
+            [-k - discriminant.sqrt(), -k + discriminant.sqrt()].map(|t| {
+                let w = [v[0] + u[0] * t, v[1] + u[1] * t];
+                graph.spans(w[1].atan2(w[0]) as f32).then_some(t)
+            })
+        },
+        GraphType::Point(_) => [None, None],
+    }
+}
+
+/// The lines through `pole` on which `a` and `b` cut a segment of `length`: the
+/// constructions of a marked ruler. They are ordered by their direction, from the x axis
+/// counterclockwise over a half turn.
+pub fn lines(length: f32, pole: WorldPoint, a: &GraphType, b: &GraphType, tolerance: &Tolerance) -> Vec<GraphType> {
+    if length <= tolerance.distance {
+        return Vec::new();
+    }
+    let (l, pole) = (length as f64, to_f64(pole));
+    let direction = |angle: f64| [angle.cos(), angle.sin()];
+
+    let mut angles = Vec::new();
+    for (i, j, sign) in (0..2).flat_map(|i| (0..2).flat_map(move |j| [(i, j, 1.0), (i, j, -1.0)])) {
+        let f = |angle: f64| {
+            let u = direction(angle);
+            Some(crossings(a, pole, u)[i]? - crossings(b, pole, u)[j]? - sign * l)
+        };
+        // Every line works when the pole is the center of a circle and on the other curve.
+        let values: Vec<f64> = (0..8).filter_map(|k| f(k as f64 * PI / 8.0)).collect();
+        if !values.is_empty() && values.iter().all(|value| value.abs() <= 1e-6 * l) {
+            continue;
+        }
+        angles.extend(roots::scan(f, 0.0, PI, SAMPLES).into_iter().map(|angle| angle % PI));
+    }
+    angles.sort_by(f64::total_cmp);
+    angles.dedup_by(|x, y| (*x - *y).abs() <= 1e-6);
+    if angles.len() > 1 && angles[0] + PI - angles[angles.len() - 1] <= 1e-6 {
+        angles.pop();
+    }
+
+    angles.into_iter().map(|angle| {
+        let u = direction(angle);
+        GraphType::Line { from: to_world(pole), to: to_world([pole[0] + u[0] * l, pole[1] + u[1] * l]) }
+    }).collect()
+}
+
+#[cfg(test)]
+mod test_neusis {
+    use super::*;
+    use super::super::intersection::intersect;
+
+    #[test]
+    fn test_trisection() {
+        // Archimedes: a line through A on the circle, cutting it again at C and the x axis
+        // at D with CD = OA, meets the axis at a third of the angle AOB.
+        let tol = Tolerance::default();
+        let angle = 75f32.to_radians();
+        let a = p(100.0 * angle.cos(), 100.0 * angle.sin());
+        let circle = GraphType::Circle { center: p(0.0, 0.0), radius: 100.0 };
+        let axis = GraphType::Line { from: p(0.0, 0.0), to: p(100.0, 0.0) };
+
+        let lines = lines(100.0, a, &circle, &axis, &tol);
+        let trisector = lines.iter().find(|line| {
+            let GraphType::Line { from, to } = **line else { unreachable!() };
+            let slope = (to.y - from.y) / (to.x - from.x);
+            (slope - 25f32.to_radians().tan()).abs() < 1e-4
+        });
+        let trisector = trisector.expect("No trisecting line");
+        let c = intersect(trisector, &circle, &tol).points().iter().copied().find(|c| (*c - a).length() > 1.0).unwrap();
+        let d = intersect(trisector, &axis, &tol).points()[0];
+        assert!(((c - d).length() - 100.0).abs() < 1e-2);
+
+        assert!(lines.windows(2).all(|pair| match pair {
+            [GraphType::Line { from: f1, to: t1 }, GraphType::Line { from: f2, to: t2 }] => {
+                (*t1 - *f1).angle_from_x_axis().radians.rem_euclid(std::f32::consts::PI)
+                    < (*t2 - *f2).angle_from_x_axis().radians.rem_euclid(std::f32::consts::PI)
+            },
+            _ => false,
+        }));
+        assert!(super::lines(0.0, a, &circle, &axis, &tol).is_empty());
+    }
+
+    #[test]
+    fn test_degenerate() {
+        // From the center of the circle, any line through it has the marks on the circle and the point.
+        let circle = GraphType::Circle { center: p(0.0, 0.0), radius: 100.0 };
+        let axis = GraphType::Line { from: p(0.0, 0.0), to: p(100.0, 0.0) };
+        assert!(lines(100.0, p(0.0, 0.0), &circle, &axis, &Tolerance::default()).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+/// The real roots of `a x^2 + b x + c`, ascending. A double root is returned once.
+pub fn quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a == 0.0 {
+        return if b == 0.0 { vec![] } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+    if discriminant == 0.0 {
+        return vec![-b / (2.0 * a)];
+    }
+    // Avoids the cancellation between `b` and the root of the discriminant.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let mut roots = vec![q / a, c / q];
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// The real roots of `a x^3 + b x^2 + c x + d`, ascending. Falls back to [`quadratic`]
+/// when the cubic term vanishes next to the others.
+pub fn cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a.abs() <= 1e-12 * (b.abs() + c.abs() + d.abs()) {
+        return quadratic(b, c, d);
+    }
+
+    // The depressed cubic t^3 + p t + q with x = t - b / 3a.
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+    let scale = q * q / 4.0 + (p * p * p / 27.0).abs();
+    let mut roots = if discriminant > 1e-12 * scale {
+        let s = discriminant.sqrt();
+        vec![(-q / 2.0 + s).cbrt() + (-q / 2.0 - s).cbrt()]
+    } else if p == 0.0 {
+        vec![0.0]
+    } else {
+        // Three real roots, two of them equal when the discriminant is zero.
+        let r = (-p / 3.0).sqrt();
+        let phi = (-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos();
+        let mut roots: Vec<_> = (0..3)
+            .map(|k| 2.0 * r * ((phi + 2.0 * std::f64::consts::PI * k as f64) / 3.0).cos())
+            .collect();
+        roots.sort_by(f64::total_cmp);
+        roots.dedup_by(|x, y| (*x - *y).abs() <= 1e-6 * r);
+        roots
+    };
+
+    // Polishes the roots with a Newton step each.
+    for root in &mut roots {
+        let x = *root - shift;
+        let f = ((x + b) * x + c) * x + d;
+        let df = (3.0 * x + 2.0 * b) * x + c;
+        if df != 0.0 && (f / df).abs() <= 1e-3 * (1.0 + x.abs()) {
+            *root -= f / df;
+        }
+        *root -= shift;
+    }
+    roots
+}
+
+/// The roots of `f` on `[from, to]`, found by sampling it `samples` times and bisecting
+/// the sign changes. `f` may be undefined in places, and jumps across which it changes
+/// sign without getting close to zero are not roots.
+pub fn scan<F>(f: F, from: f64, to: f64, samples: usize) -> Vec<f64>
+where
+    F: Fn(f64) -> Option<f64>
+{
+    let step = (to - from) / samples as f64;
+    let mut roots = Vec::new();
+    let mut last = f(from).map(|value| (from, value));
+    for i in 1..=samples {
+        let x = from + step * i as f64;
+        let value = f(x);
+        if let (Some((mut lo, mut f_lo)), Some(f_hi)) = (last, value) {
+            if f_lo == 0.0 {
+                roots.push(lo);
+            } else if f_lo.signum() != f_hi.signum() && f_hi != 0.0 {
+                let mut hi = x;
+                for _ in 0..60 {
+                    let mid = (lo + hi) / 2.0;
+                    let Some(f_mid) = f(mid) else { break };
+                    if f_mid.signum() == f_lo.signum() {
+                        (lo, f_lo) = (mid, f_mid);
+                    } else {
+                        hi = mid;
+                    }
+                }
+                let root = (lo + hi) / 2.0;
+                if f(root).is_some_and(|value| value.abs() <= 1e-6 * (1.0 + f_lo.abs().max(f_hi.abs()))) {
+                    roots.push(root);
+                }
+            }
+        }
+        last = value.map(|value| (x, value));
+    }
+    roots
+}
+
+#[cfg(test)]
+mod test_roots {
+    use super::*;
+
+    fn assert_roots(roots: Vec<f64>, expected: &[f64]) {
+        assert_eq!(roots.len(), expected.len(), "{:?}", roots);
+        roots.iter().zip(expected).for_each(|(a, b)| assert!((a - b).abs() < 1e-9, "{:?} != {:?}", roots, expected));
+    }
+
+    #[test]
+    fn test_polynomials() {
+        assert_roots(quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(quadratic(1.0, 2.0, 1.0), &[-1.0]);
+        assert_roots(quadratic(1.0, 0.0, 1.0), &[]);
+        assert_roots(cubic(1.0, -6.0, 11.0, -6.0), &[1.0, 2.0, 3.0]);
+        assert_roots(cubic(1.0, 0.0, 0.0, -2.0), &[2f64.cbrt()]);
+        assert_roots(cubic(2.0, -2.0, 0.0, 0.0), &[0.0, 1.0]);
+        assert_roots(cubic(0.0, 1.0, 0.0, -4.0), &[-2.0, 2.0]);
+    }
+
+    #[test]
+    fn test_scan() {
+        assert_roots(scan(|x| Some(x.sin()), -1.0, 7.0, 100), &[0.0, std::f64::consts::PI, 2.0 * std::f64::consts::PI]);
+        // The pole of the tangent is a sign change but not a root.
+        assert_roots(scan(|x| Some(x.tan()), 1.0, 4.0, 100), &[std::f64::consts::PI]);
+        assert_roots(scan(|x| (x > 2.0).then_some(x - 3.0), 0.0, 4.0, 10), &[3.0]);
+    }
+}
@@ -0,0 +1,75 @@
+use super::prelude::*;
+
+/// How the compass behaves when lifted off the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compass {
+    /// Euclid's compass, which collapses: a circle is drawn through a point around a center.
+    Collapsing,
+    /// Also keeps its opening, to draw a circle with the radius between two other points.
+    Rigid,
+}
+
+/// The instruments a construction may use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Without it, lines can't be drawn and a line is only known by two points on it,
+    /// as in the Mohr–Mascheroni constructions.
+    pub straightedge: bool,
+    pub compass: Compass,
+    /// The marked ruler, which slides a segment between two curves.
+    pub neusis: bool,
+    /// Folding along the creases of the origami axioms.
+    pub origami: bool,
+}
+
+impl Rules {
+    pub const EUCLID: Rules = Rules { straightedge: true, compass: Compass::Collapsing, neusis: false, origami: false };
+    pub const COMPASS_ONLY: Rules = Rules { straightedge: false, ..Self::EUCLID };
+    pub const RIGID: Rules = Rules { compass: Compass::Rigid, ..Self::EUCLID };
+    pub const NEUSIS: Rules = Rules { neusis: true, ..Self::EUCLID };
+    pub const ORIGAMI: Rules = Rules { origami: true, ..Self::EUCLID };
+
+    /// Whether some instrument makes lines. Otherwise a line is only known by two points on it.
+    pub fn draws_lines(&self) -> bool {
+        self.straightedge || self.neusis || self.origami
+    }
+
+    /// Whether everything constructible is within square roots, as [`super::exact`] computes.
+    pub fn quadratic(&self) -> bool {
+        !self.neusis && !self.origami
+    }
+
+    /// Whether the instrument making `definition` is allowed, whatever it is built on.
+    pub fn has_instrument(&self, definition: &Definition) -> bool {
+        match definition {
+            Definition::Line { .. } | Definition::Ray { .. } | Definition::Segment { .. } => self.straightedge,
+            Definition::CircleRadius { .. } => self.compass == Compass::Rigid,
+            Definition::Neusis { .. } => self.neusis,
+            Definition::Fold(_) => self.origami,
+            _ => true,
+        }
+    }
+
+    /// Whether `definition` may be added to `construction`.
+    pub fn allows(&self, definition: &Definition, construction: &Construction) -> bool {
+        let is_line = |id: ObjectId| construction.get(id).is_some_and(|object| object.definition.kind() == ObjectKind::Line);
+        self.has_instrument(definition) && match *definition {
+            Definition::Intersection { a, b, .. } => self.draws_lines() || !(is_line(a) || is_line(b)),
+            Definition::PointOn { object, .. } => self.draws_lines() || !is_line(object),
+            _ => true,
+        }
+    }
+
+    /// Whether the objects from `first` on only use the allowed instruments.
+    pub fn check(&self, construction: &Construction, first: ObjectId) -> bool {
+        construction.iter()
+            .filter(|(id, _)| *id >= first)
+            .all(|(_, object)| self.allows(&object.definition, construction))
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::EUCLID
+    }
+}
@@ -0,0 +1,34 @@
+use super::color::Rgba8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub stroke: Rgba8,
+    pub width: f32,
+    /// Lengths of the dashes and of the gaps between them. Solid when `None`.
+    pub dash: Option<[f32; 2]>,
+    /// Fill of closed curves.
+    pub fill: Option<Rgba8>,
+}
+
+impl Style {
+    /// Radius of the marker drawn for a point.
+    pub fn point_radius(&self) -> f32 {
+        self.width * 0.8
+    }
+
+    /// The dash pattern if it can be drawn, ignoring patterns without a visible dash or gap.
+    pub fn dash_pattern(&self) -> Option<[f32; 2]> {
+        self.dash.filter(|[dash, gap]| *dash > 0.0 && *gap > 0.0)
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            stroke: Rgba8::new(0xff0000ff),
+            width: 10.0,
+            dash: None,
+            fill: None,
+        }
+    }
+}
@@ -2,3 +2,4 @@ pub mod app;
 pub mod scene_system;
 pub mod data;
 pub mod renderer;
+pub mod puzzle;
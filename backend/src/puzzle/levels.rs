@@ -0,0 +1,245 @@
+use crate::{
+    data::{
+        prelude::*,
+        rules::Rules,
+        fold::{ Fold, Axiom },
+    },
+    scene_system::tool::ToolKind,
+};
+use super::{ Level, Score };
+
+/// Adds a labeled free point.
+fn point(c: &mut Construction, label: &str, x: f32, y: f32) -> ObjectId {
+    let id = c.add(Definition::FreePoint(WorldPoint::new(x, y)));
+    c.set_label(id, Some(String::from(label)));
+    id
+}
+
+/// Adds the perpendicular bisector of `a` and `b`.
+fn bisector(c: &mut Construction, a: ObjectId, b: ObjectId) -> ObjectId {
+    let ca = c.add(Definition::Circle { center: a, through: b });
+    let cb = c.add(Definition::Circle { center: b, through: a });
+    let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+    let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+    c.add(Definition::Line { from: top, to: bottom })
+}
+
+/// The built-in levels, easiest first.
+pub fn levels() -> Vec<Level> {
+    vec![
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -100.0, 0.0);
+            let b = point(&mut givens, "B", 100.0, 0.0);
+            givens.add(Definition::Line { from: a, to: b });
+            Level::new(
+                "Equilateral triangle",
+                "Construct an equilateral triangle with side AB, above AB.",
+                givens,
+                Score { l: 4, e: 4 },
+                |c| {
+                    let ca = c.add(Definition::Circle { center: a, through: b });
+                    let cb = c.add(Definition::Circle { center: b, through: a });
+                    let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+                    vec![c.add(Definition::Line { from: a, to: top }), c.add(Definition::Line { from: b, to: top })]
+                },
+            )
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -100.0, -20.0);
+            let b = point(&mut givens, "B", 80.0, 40.0);
+            Level::new(
+                "Perpendicular bisector",
+                "Construct the perpendicular bisector of A and B.",
+                givens,
+                Score { l: 3, e: 3 },
+                |c| vec![bisector(c, a, b)],
+            ).unlocking(ToolKind::PerpendicularBisector)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -120.0, 0.0);
+            let b = point(&mut givens, "B", 120.0, 30.0);
+            let ab = givens.add(Definition::Line { from: a, to: b });
+            Level::new(
+                "Midpoint",
+                "Construct the midpoint of AB.",
+                givens,
+                Score { l: 3, e: 3 },
+                |c| {
+                    let line = bisector(c, a, b);
+                    vec![c.add(Definition::Intersection { a: line, b: ab, index: 0 })]
+                },
+            ).unlocking(ToolKind::Midpoint)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -150.0, 0.0);
+            let b = point(&mut givens, "B", 150.0, 0.0);
+            let ab = givens.add(Definition::Line { from: a, to: b });
+            let o = givens.add(Definition::PointOn { object: ab, param: 0.4 });
+            givens.set_label(o, Some(String::from("O")));
+            Level::new(
+                "Perpendicular at a point",
+                "Construct the line through O perpendicular to AB.",
+                givens,
+                Score { l: 4, e: 4 },
+                |c| {
+                    let circle = c.add(Definition::Circle { center: o, through: a });
+                    // The second intersection along AB is the reflection of A about O.
+                    let mirror = c.add(Definition::Intersection { a: circle, b: ab, index: 1 });
+                    vec![bisector(c, a, mirror)]
+                },
+            ).unlocking(ToolKind::Perpendicular)
+        },
+        {
+            let mut givens = Construction::new();
+            let v = point(&mut givens, "V", -100.0, -60.0);
+            let a = point(&mut givens, "A", 120.0, -40.0);
+            let b = point(&mut givens, "B", 20.0, 120.0);
+            givens.add(Definition::Line { from: v, to: a });
+            let vb = givens.add(Definition::Line { from: v, to: b });
+            Level::new(
+                "Angle bisector",
+                "Construct the bisector of the angle AVB.",
+                givens,
+                Score { l: 4, e: 4 },
+                |c| {
+                    let circle = c.add(Definition::Circle { center: v, through: a });
+                    // Along VB, the second intersection is on the side of B.
+                    let b = c.add(Definition::Intersection { a: circle, b: vb, index: 1 });
+                    let ca = c.add(Definition::Circle { center: a, through: v });
+                    let cb = c.add(Definition::Circle { center: b, through: v });
+                    let far = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+                    vec![c.add(Definition::Line { from: v, to: far })]
+                },
+            ).unlocking(ToolKind::AngleBisector)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -120.0, -40.0);
+            let b = point(&mut givens, "B", 120.0, -40.0);
+            let ab = givens.add(Definition::Line { from: a, to: b });
+            let p = point(&mut givens, "P", -30.0, 60.0);
+            Level::new(
+                "Parallel line",
+                "Construct the line through P parallel to AB.",
+                givens,
+                Score { l: 4, e: 4 },
+                |c| {
+                    // A rhombus with the sides AP and AC.
+                    let circle = c.add(Definition::Circle { center: a, through: p });
+                    let corner = c.add(Definition::Intersection { a: circle, b: ab, index: 1 });
+                    let cp = c.add(Definition::Circle { center: p, through: a });
+                    let cc = c.add(Definition::Circle { center: corner, through: a });
+                    let last = c.add(Definition::Intersection { a: cp, b: cc, index: 0 });
+                    vec![c.add(Definition::Line { from: p, to: last })]
+                },
+            ).unlocking(ToolKind::Parallel)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -100.0, -30.0);
+            let b = point(&mut givens, "B", 100.0, -30.0);
+            givens.add(Definition::Line { from: a, to: b });
+            let p = point(&mut givens, "P", -20.0, 50.0);
+            Level::new(
+                "Reflection",
+                "Without the straightedge, construct the reflection of P over AB.",
+                givens,
+                Score { l: 2, e: 2 },
+                |c| {
+                    let ca = c.add(Definition::Circle { center: a, through: p });
+                    let cb = c.add(Definition::Circle { center: b, through: p });
+                    vec![c.add(Definition::Intersection { a: ca, b: cb, index: 1 })]
+                },
+            ).with_rules(Rules::COMPASS_ONLY)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -60.0, 0.0);
+            let b = point(&mut givens, "B", 40.0, 0.0);
+            Level::new(
+                "Doubling a segment",
+                "Without the straightedge, construct C on the line AB such that B is the midpoint of AC.",
+                givens,
+                Score { l: 3, e: 3 },
+                |c| {
+                    // The opposite corners of two equilateral triangles on AB are sqrt(3) AB apart, like A and C.
+                    let cb = c.add(Definition::Circle { center: b, through: a });
+                    let ca = c.add(Definition::Circle { center: a, through: b });
+                    let top = c.add(Definition::Intersection { a: cb, b: ca, index: 0 });
+                    let bottom = c.add(Definition::Intersection { a: cb, b: ca, index: 1 });
+                    let far = c.add(Definition::Circle { center: top, through: bottom });
+                    vec![c.add(Definition::Intersection { a: cb, b: far, index: 0 })]
+                },
+            ).with_rules(Rules::COMPASS_ONLY)
+        },
+        {
+            let mut givens = Construction::new();
+            let a = point(&mut givens, "A", -120.0, 60.0);
+            let b = point(&mut givens, "B", -40.0, 90.0);
+            let c = point(&mut givens, "C", -60.0, -40.0);
+            let d = point(&mut givens, "D", 120.0, -40.0);
+            let cd = givens.add(Definition::Line { from: c, to: d });
+            Level::new(
+                "Copy a segment",
+                "With a rigid compass, construct E on CD, on the side of D, such that CE is as long as AB.",
+                givens,
+                Score { l: 1, e: 1 },
+                |s| {
+                    let circle = s.add(Definition::CircleRadius { center: c, from: a, to: b });
+                    vec![s.add(Definition::Intersection { a: circle, b: cd, index: 1 })]
+                },
+            ).with_rules(Rules::RIGID)
+        },
+        {
+            let mut givens = Construction::new();
+            let o = point(&mut givens, "O", 0.0, 0.0);
+            let angle = 75f32.to_radians();
+            let a = point(&mut givens, "A", 100.0 * angle.cos(), 100.0 * angle.sin());
+            let b = point(&mut givens, "B", 140.0, 0.0);
+            let circle = givens.add(Definition::Circle { center: o, through: a });
+            let ob = givens.add(Definition::Line { from: o, to: b });
+            Level::new(
+                "Angle trisection",
+                "With a marked ruler, construct the line through O at a third of the angle AOB from OB.",
+                givens,
+                Score { l: 2, e: 2 },
+                |c| {
+                    // Archimedes: the line through A cutting the circle again at C and the line OB at D,
+                    // with CD = OA. The triangle OCD is isosceles, so the angle DOC is a third of AOB.
+                    let ruler = c.add(Definition::Neusis { from: o, to: a, pole: a, a: circle, b: ob, index: 0 });
+                    let far = c.add(Definition::Intersection { a: ruler, b: circle, index: 0 });
+                    vec![c.add(Definition::Line { from: o, to: far })]
+                },
+            ).with_rules(Rules::NEUSIS)
+        },
+        {
+            let mut givens = Construction::new();
+            let o = point(&mut givens, "O", 0.0, 0.0);
+            let u = point(&mut givens, "U", 50.0, 0.0);
+            let axis = givens.add(Definition::Line { from: o, to: u });
+            let p = point(&mut givens, "P", 0.0, 50.0);
+            let l1 = givens.add(Definition::FreePoint(WorldPoint::new(-50.0, -50.0)));
+            let l2 = givens.add(Definition::FreePoint(WorldPoint::new(50.0, -50.0)));
+            let l = givens.add(Definition::Line { from: l1, to: l2 });
+            let q = point(&mut givens, "Q", -100.0, 0.0);
+            let m1 = givens.add(Definition::FreePoint(WorldPoint::new(100.0, -50.0)));
+            let m2 = givens.add(Definition::FreePoint(WorldPoint::new(100.0, 50.0)));
+            let m = givens.add(Definition::Line { from: m1, to: m2 });
+            Level::new(
+                "Doubling the cube",
+                "By folding, construct X on OU with OX = 2^(1/3) OU: fold P onto the bottom line and Q onto the right line at once.",
+                givens,
+                Score { l: 1, e: 1 },
+                |c| {
+                    // Beloch's fold has the slope 2^(1/3) and crosses OU at that distance.
+                    let crease = c.add(Definition::Fold(Fold { axiom: Axiom::TwoPointsToLines, inputs: vec![p, l, q, m], index: 0 }));
+                    vec![c.add(Definition::Intersection { a: crease, b: axis, index: 0 })]
+                },
+            ).with_rules(Rules::ORIGAMI)
+        },
+    ]
+}
@@ -0,0 +1,555 @@
+use crate::{
+    data::{
+        prelude::*,
+        construction::Object,
+        intersection::{ intersect, distance_to },
+        exact::{ Tower, Evaluation, ExactGraph },
+        rules::Rules,
+        fold::Axiom,
+    },
+    scene_system::tool::ToolKind,
+};
+
+mod levels;
+pub use levels::levels;
+
+pub mod random;
+use random::Random;
+
+pub mod progress;
+use progress::Progress;
+
+pub mod solver;
+
+/// Looser than the construction tolerance, since the player reaches the goal
+/// through other, longer chains of intersections than the reference solution.
+pub const GOAL_TOLERANCE: Tolerance = Tolerance { distance: 1e-2, angle: 1e-4 };
+
+/// The most states the search for a hint explores. The hint is searched for within a frame
+/// of the game, and the start of every level takes far fewer.
+pub const HINT_BUDGET: usize = 2000;
+
+/// Number of random configurations a solution is checked in.
+pub const TRIALS: usize = 8;
+
+/// The tools available before any level is solved.
+pub const BASIC_TOOLS: [ToolKind; 4] = [ToolKind::Move, ToolKind::Point, ToolKind::Straightedge, ToolKind::Compass];
+
+/// Move counts of a solution. Every line or circle is one elementary move (E), including
+/// the hidden steps of macro tools but not the lines they extend rays and segments by,
+/// and every use of a tool is one tool move (L).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Score {
+    pub l: usize,
+    pub e: usize,
+}
+
+/// A puzzle: the objects the player starts with and the objects to construct.
+pub struct Level {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The objects the player starts with.
+    pub givens: Construction,
+    /// A reference construction extending the givens, in which the goals are defined.
+    pub solution: Construction,
+    pub goals: Vec<ObjectId>,
+    /// The counts to beat for the stars.
+    pub target: Score,
+    /// The macro tool earned by solving the level.
+    pub unlocks: Option<ToolKind>,
+    /// The instruments allowed. Without any drawing lines, a goal line is reached
+    /// by constructing two points on it.
+    pub rules: Rules,
+}
+
+impl Level {
+    /// Builds a level from the givens and the steps of a reference solution, which returns the goals.
+    pub fn new<F>(name: &'static str, description: &'static str, givens: Construction, target: Score, solve: F) -> Self
+    where
+        F: FnOnce(&mut Construction) -> Vec<ObjectId>
+    {
+        let mut solution = givens.clone();
+        let goals = solve(&mut solution);
+        solution.update();
+        Self { name, description, givens, solution, goals, target, unlocks: None, rules: Rules::default() }
+    }
+
+    pub fn unlocking(self, tool: ToolKind) -> Self {
+        Self { unlocks: Some(tool), ..self }
+    }
+
+    pub fn with_rules(self, rules: Rules) -> Self {
+        Self { rules, ..self }
+    }
+
+    /// Whether a goal line is only represented by the points on it.
+    fn by_points(&self, target: &GraphType) -> bool {
+        !self.rules.draws_lines() && target.is_line()
+    }
+
+    /// Counts the moves the player made on top of the givens.
+    pub fn score(&self, construction: &Construction) -> Score {
+        let added = || construction.iter().filter(|(id, _)| *id >= self.givens.next_id());
+        // A hidden line under a ray or a segment only lets macro steps reach beyond its ends.
+        let extends = |object: &Object| object.hidden && match object.definition {
+            Definition::Line { from, to } => construction.iter().any(|(_, other)| matches!(
+                other.definition,
+                Definition::Ray { from: f, to: t } | Definition::Segment { from: f, to: t } if (f, t) == (from, to)
+            )),
+            _ => false,
+        };
+        let e = added()
+            .filter(|(_, object)| object.definition.kind() != ObjectKind::Point && !extends(object))
+            .count();
+        // The result of a macro is the only visible object built on its hidden steps.
+        let l = added()
+            .filter(|(_, object)| !object.hidden)
+            .filter(|(_, object)| {
+                object.definition.kind() != ObjectKind::Point || object.definition.dependencies().iter()
+                    .any(|id| construction.get(*id).is_some_and(|dependency| dependency.hidden))
+            })
+            .count();
+        Score { l, e }
+    }
+
+    /// One star for solving, one for reaching the L target and one for the E target.
+    pub fn stars(&self, best: Option<Score>) -> usize {
+        best.map_or(0, |best| 1 + (best.l <= self.target.l) as usize + (best.e <= self.target.e) as usize)
+    }
+
+    /// The construction the player starts with.
+    pub fn start(&self) -> Construction {
+        let mut construction = self.givens.clone();
+        construction.update();
+        construction
+    }
+
+    /// The reference solution for the givens as they are placed in `construction`,
+    /// which extends the givens. `None` if a given was deleted.
+    fn placed(&self, construction: &Construction) -> Option<Construction> {
+        let mut solution = self.solution.clone();
+        for (id, given) in self.givens.iter() {
+            match (&given.definition, &construction.get(id)?.definition) {
+                (Definition::FreePoint(_), Definition::FreePoint(pos)) => { solution.move_point(id, *pos); },
+                (Definition::PointOn { object, .. }, Definition::PointOn { object: other, param }) if object == other => {
+                    solution.set_param(id, *param);
+                },
+                (definition, other) if definition == other => {},
+                _ => return None,
+            }
+        }
+        solution.update();
+        Some(solution)
+    }
+
+    /// The goals for the givens as they are placed in `construction`.
+    pub fn targets(&self, construction: &Construction) -> Option<Vec<GraphType>> {
+        let solution = self.placed(construction)?;
+        self.goals.iter().map(|goal| solution.graph(*goal)).collect()
+    }
+
+    /// The next line or circle of a shortest way to the goals from `construction`,
+    /// if there is one within the target E count. Fails when the search takes longer
+    /// than [`HINT_BUDGET`] allows.
+    pub fn hint(&self, construction: &Construction) -> anyhow::Result<Option<GraphType>> {
+        let Some(targets) = self.targets(construction) else { return Ok(None) };
+        let Some(steps) = solver::solve_within(construction, &targets, self.target.e, &self.rules, HINT_BUDGET)? else {
+            return Ok(None);
+        };
+        // The first move only uses objects of the construction.
+        let first = steps.iter().find(|step| step.kind() != ObjectKind::Point);
+        Ok(first.and_then(|first| first.evaluate(|id| construction.graph(id), &construction.tolerance)))
+    }
+
+    /// The objects of `construction` coinciding with each goal, or the points on it for a goal
+    /// line without a straightedge. `None` if the construction breaks the rules.
+    fn matches(&self, construction: &Construction) -> Option<Vec<Vec<ObjectId>>> {
+        if !self.rules.check(construction, self.givens.next_id()) {
+            return None;
+        }
+        let targets = self.targets(construction)?;
+        Some(targets.iter().map(|target| {
+            construction.iter()
+                .filter(|(_, object)| !object.hidden)
+                .filter(|(_, object)| object.graph().is_some_and(|graph| match graph {
+                    GraphType::Point(pos) if self.by_points(target) => distance_to(target, pos) <= GOAL_TOLERANCE.distance,
+                    graph => matches!(intersect(&graph, target, &GOAL_TOLERANCE), Intersection::Coincident),
+                }))
+                .map(|(id, _)| id)
+                .collect()
+        }).collect())
+    }
+
+    /// The matches which are the goals exactly, in constructible numbers.
+    fn matches_exact(&self, construction: &Construction) -> Option<Vec<Vec<ObjectId>>> {
+        let mut matches = self.matches(construction)?;
+        let solution = self.placed(construction)?;
+        let mut tower = Tower::new();
+        let mut targets = Evaluation::new(&solution);
+        let mut objects = Evaluation::new(construction);
+        for (goal, ids) in self.goals.iter().zip(&mut matches) {
+            let target = targets.graph(*goal, &mut tower)?;
+            let by_points = !self.rules.draws_lines() && target.is_line();
+            ids.retain(|id| objects.graph(*id, &mut tower).is_some_and(|graph| match graph {
+                ExactGraph::Point(p) if by_points => tower.contains(&target, &p),
+                graph => tower.same(&target, &graph),
+            }));
+        }
+        Some(matches)
+    }
+
+    /// Whether `construction` solves the level in general, not just by coincidence: the objects
+    /// matching the goals have to keep matching them when the free points are moved at random.
+    pub fn check(&self, construction: &Construction) -> bool {
+        self.check_with(construction, &mut Random::new(0x5eed), TRIALS)
+    }
+
+    pub fn check_with(&self, construction: &Construction, random: &mut Random, trials: usize) -> bool {
+        self.verify(construction, random, trials, Self::matches)
+    }
+
+    /// Like [`Self::check`], with the goals compared exactly rather than within [`GOAL_TOLERANCE`].
+    /// The rules beyond square roots are only checked within the tolerance.
+    pub fn check_exact(&self, construction: &Construction) -> bool {
+        if !self.rules.quadratic() {
+            return self.check(construction);
+        }
+        self.verify(construction, &mut Random::new(0x5eed), TRIALS, Self::matches_exact)
+    }
+
+    /// How far the free points move when a solution is checked: a tenth of the extent
+    /// of the given points, whichever way they are spread.
+    fn perturbation(&self) -> f32 {
+        let points: Vec<_> = self.givens.iter().filter_map(|(_, object)| match object.graph() {
+            Some(GraphType::Point(pos)) => Some(pos),
+            _ => None,
+        }).collect();
+        let size = WorldRect::from_points(points).size();
+        size.width.max(size.height).max(1.0) * 0.1
+    }
+
+    fn verify<F>(&self, construction: &Construction, random: &mut Random, trials: usize, matches: F) -> bool
+    where
+        F: Fn(&Self, &Construction) -> Option<Vec<Vec<ObjectId>>>
+    {
+        let Some(mut candidates) = matches(self, construction) else { return false };
+        let range = self.perturbation();
+
+        let mut checked = 0;
+        for _ in 0..trials {
+            if candidates.iter().any(|ids| ids.is_empty()) {
+                return false;
+            }
+
+            let mut perturbed = construction.clone();
+            for (id, object) in construction.iter() {
+                match object.definition {
+                    Definition::FreePoint(pos) => {
+                        perturbed.move_point(id, pos + WorldVector::new(random.offset(range), random.offset(range)));
+                    },
+                    Definition::PointOn { param, .. } => {
+                        perturbed.set_param(id, param + random.offset(0.1));
+                    },
+                    _ => {},
+                }
+            }
+            perturbed.update();
+
+            // Skip the configurations where the goals themselves don't exist.
+            let Some(matches) = matches(self, &perturbed) else { continue };
+            checked += 1;
+            for (ids, matched) in candidates.iter_mut().zip(matches) {
+                ids.retain(|id| matched.contains(id));
+            }
+        }
+
+        // A match that was never put to the test proves nothing.
+        if checked == 0 {
+            return false;
+        }
+
+        // A goal line represented by points needs two distinct ones.
+        let Some(targets) = self.targets(construction) else { return false };
+        targets.iter().zip(&candidates).all(|(target, ids)| match self.by_points(target) {
+            true => ids.iter().filter_map(|id| construction.graph(*id)).any(|a| {
+                ids.iter().filter_map(|id| construction.graph(*id)).any(|b| match (a, b) {
+                    (GraphType::Point(a), GraphType::Point(b)) => (a - b).length() > GOAL_TOLERANCE.distance,
+                    _ => false,
+                })
+            }),
+            false => !ids.is_empty(),
+        })
+    }
+}
+
+/// The basic tools, the other instruments and the macro tools unlocked by the solved levels,
+/// as far as `rules` allow them.
+pub fn tools(levels: &[Level], progress: &Progress, rules: &Rules) -> Vec<ToolKind> {
+    let unlocked = levels.iter()
+        .filter(|level| progress.best(level.name).is_some())
+        .filter_map(|level| level.unlocks);
+    BASIC_TOOLS.into_iter()
+        .chain([ToolKind::RigidCompass, ToolKind::Neusis])
+        .chain(Axiom::ALL.map(ToolKind::Fold))
+        .chain(unlocked)
+        .filter(|tool| tool.allowed(rules))
+        .collect()
+}
+
+#[cfg(test)]
+mod test_puzzle {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        for level in levels() {
+            // The reference solution solves its own level, and the givens alone don't.
+            assert!(level.check(&level.solution), "{}", level.name);
+            assert!(!level.check(&level.start()), "{}", level.name);
+            assert_eq!(level.score(&level.solution), level.target, "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn test_optimal() {
+        for level in levels() {
+            // The targets can't be beaten, so the solver needs exactly as many moves.
+            let start = level.start();
+            let targets = level.targets(&start).unwrap();
+            assert_eq!(solver::solve(&start, &targets, level.target.e - 1, &level.rules), None, "{}", level.name);
+            let steps = solver::solve(&start, &targets, level.target.e, &level.rules).unwrap();
+            assert_eq!(steps.iter().filter(|step| step.kind() != ObjectKind::Point).count(), level.target.e, "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        let level = levels().into_iter().find(|level| level.name == "Perpendicular bisector").unwrap();
+        let mut c = level.start();
+        let (a, b) = (ObjectId(0), ObjectId(1));
+        let length = match (c.graph(a), c.graph(b)) {
+            (Some(GraphType::Point(a)), Some(GraphType::Point(b))) => (b - a).length(),
+            _ => unreachable!(),
+        };
+        match level.hint(&c).unwrap() {
+            Some(GraphType::Circle { radius, .. }) => assert!((radius - length).abs() < 1e-3),
+            other => panic!("Expected circle, got {:?}", other),
+        }
+
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+        c.update();
+        let hint = level.hint(&c).unwrap().unwrap();
+        assert!(matches!(intersect(&hint, &level.targets(&c).unwrap()[0], &GOAL_TOLERANCE), Intersection::Coincident));
+
+        // Nothing is left to do once solved.
+        c.add(Definition::Line { from: ObjectId(4), to: ObjectId(5) });
+        c.update();
+        assert_eq!(level.hint(&c).unwrap(), None);
+
+        // The hints for the start of every level are found within the budget.
+        for level in levels() {
+            assert!(level.hint(&level.start()).unwrap().is_some(), "{}", level.name);
+        }
+    }
+
+    #[test]
+    fn test_exact() {
+        for level in levels() {
+            assert!(level.check_exact(&level.solution), "{}", level.name);
+            assert!(!level.check_exact(&level.start()), "{}", level.name);
+        }
+
+        // A point put within the tolerance of the midpoint matches, but not exactly.
+        let level = levels().into_iter().find(|level| level.name == "Midpoint").unwrap();
+        let mut c = level.start();
+        let Some(GraphType::Point(midpoint)) = level.targets(&c).unwrap().first().copied() else { panic!("Expected point") };
+        let near = c.add(Definition::FreePoint(midpoint + WorldVector::new(1e-3, 0.0)));
+        c.update();
+        assert_eq!(level.matches(&c).unwrap()[0], vec![near]);
+        assert_eq!(level.matches_exact(&c).unwrap()[0], vec![]);
+    }
+
+    #[test]
+    fn test_check() {
+        let level = levels().into_iter().find(|level| level.name == "Perpendicular bisector").unwrap();
+        let mut c = level.start();
+        let (a, b) = (ObjectId(0), ObjectId(1));
+
+        // A line through the right points, found another way.
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+        let wrong = c.add(Definition::Line { from: top, to: a });
+        c.update();
+        assert!(!level.check(&c));
+
+        c.remove(&[wrong]);
+        c.add(Definition::Line { from: bottom, to: top });
+        c.update();
+        assert!(level.check(&c));
+
+        // The goal follows the givens.
+        c.move_point(b, WorldPoint::new(0.0, 300.0));
+        c.update();
+        assert!(level.check(&c));
+
+        c.remove(&[b]);
+        assert!(!level.check(&c));
+    }
+
+    #[test]
+    fn test_score() {
+        let level = levels().into_iter().find(|level| level.name == "Perpendicular bisector").unwrap();
+        let mut c = level.start();
+        assert_eq!(level.score(&c), Score { l: 0, e: 0 });
+
+        // Points are free, lines and circles are not.
+        let a = c.add(Definition::FreePoint(WorldPoint::new(0.0, 0.0)));
+        c.add(Definition::Circle { center: a, through: ObjectId(0) });
+        c.add(Definition::Line { from: a, to: ObjectId(1) });
+        assert_eq!(level.score(&c), Score { l: 2, e: 2 });
+
+        assert_eq!(level.stars(None), 0);
+        assert_eq!(level.stars(Some(Score { l: 5, e: 5 })), 1);
+        assert_eq!(level.stars(Some(Score { l: 3, e: 4 })), 2);
+        assert_eq!(level.stars(Some(level.target)), 3);
+    }
+
+    #[test]
+    fn test_coincidence() {
+        let level = levels().into_iter().find(|level| level.name == "Midpoint").unwrap();
+        let targets = level.targets(&level.start()).unwrap();
+        let Some(GraphType::Point(midpoint)) = targets.first().copied() else { panic!("Expected point") };
+
+        // A point put right on the midpoint is only there by chance.
+        let mut c = level.start();
+        c.add(Definition::FreePoint(midpoint));
+        c.update();
+        assert!(level.matches(&c).unwrap()[0].len() == 1);
+        assert!(!level.check(&c));
+
+        // A point on AB looks right too.
+        let mut c = level.start();
+        c.add(Definition::PointOn { object: ObjectId(2), param: 0.5 });
+        c.update();
+        assert!(!level.check(&c));
+
+        // Extra free points used in the construction move too, and the solution still holds.
+        let mut c = level.start();
+        let (a, b) = (ObjectId(0), ObjectId(1));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+        let bisector = c.add(Definition::Line { from: top, to: bottom });
+        let aside = c.add(Definition::FreePoint(WorldPoint::new(300.0, 300.0)));
+        let helper = c.add(Definition::Circle { center: aside, through: a });
+        c.add(Definition::Intersection { a: bisector, b: ObjectId(2), index: 0 });
+        c.add(Definition::Intersection { a: helper, b: ObjectId(2), index: 0 });
+        c.update();
+        assert!(level.check_with(&c, &mut Random::new(1), 50));
+    }
+
+    #[test]
+    fn test_perturbation() {
+        // Givens on a vertical line move as far as those on a horizontal one.
+        let mut givens = Construction::new();
+        let a = givens.add(Definition::FreePoint(WorldPoint::new(0.0, -150.0)));
+        let b = givens.add(Definition::FreePoint(WorldPoint::new(0.0, 150.0)));
+        let level = Level::new("Vertical", "", givens, Score { l: 1, e: 1 }, |c| vec![c.add(Definition::Line { from: a, to: b })]);
+        assert_eq!(level.perturbation(), 30.0);
+
+        // Goals missing from every perturbed configuration leave the solution unchecked.
+        let mut c = level.start();
+        c.add(Definition::Line { from: a, to: b });
+        c.update();
+        assert!(level.check(&c));
+        let calls = std::cell::Cell::new(0);
+        let first_only = |level: &Level, c: &Construction| {
+            calls.set(calls.get() + 1);
+            if calls.get() == 1 { level.matches(c) } else { None }
+        };
+        assert!(!level.verify(&c, &mut Random::new(1), TRIALS, first_only));
+        assert_eq!(calls.get(), TRIALS + 1);
+    }
+
+    #[test]
+    fn test_macro_score() {
+        use crate::scene_system::macros::expand;
+
+        for (name, tool, inputs) in [
+            ("Perpendicular bisector", ToolKind::PerpendicularBisector, vec![ObjectId(0), ObjectId(1)]),
+            ("Midpoint", ToolKind::Midpoint, vec![ObjectId(0), ObjectId(1)]),
+            ("Perpendicular at a point", ToolKind::Perpendicular, vec![ObjectId(2), ObjectId(3)]),
+            ("Angle bisector", ToolKind::AngleBisector, vec![ObjectId(1), ObjectId(0), ObjectId(2)]),
+            ("Parallel line", ToolKind::Parallel, vec![ObjectId(2), ObjectId(3)]),
+        ] {
+            let level = levels().into_iter().find(|level| level.name == name).unwrap();
+            let mut c = level.start();
+            expand(tool, &inputs, &mut c).unwrap();
+            c.update();
+
+            // One tool move, and the elementary moves of the steps it stands for.
+            assert!(level.check(&c), "{}", name);
+            assert_eq!(level.score(&c), Score { l: 1, e: level.target.e }, "{}", name);
+        }
+
+        // The line extending a segment for the steps is no move of its own.
+        let level = levels().into_iter().find(|level| level.name == "Perpendicular bisector").unwrap();
+        let mut c = level.start();
+        let segment = c.add(Definition::Segment { from: ObjectId(0), to: ObjectId(1) });
+        let point = c.add(Definition::FreePoint(WorldPoint::new(20.0, 70.0)));
+        c.update();
+        expand(ToolKind::Perpendicular, &[segment, point], &mut c).unwrap();
+        assert!(c.iter().any(|(_, object)| object.hidden && object.definition == Definition::Line { from: ObjectId(0), to: ObjectId(1) }));
+        // The segment, the two circles of the mirror image and the perpendicular.
+        assert_eq!(level.score(&c), Score { l: 2, e: 4 });
+    }
+
+    #[test]
+    fn test_tools() {
+        let levels = levels();
+        let mut progress = Progress::new();
+        assert_eq!(tools(&levels, &progress, &Rules::EUCLID), BASIC_TOOLS.to_vec());
+
+        progress.record("Midpoint", Score { l: 3, e: 3 });
+        progress.record("Parallel line", Score { l: 5, e: 5 });
+        assert_eq!(tools(&levels, &progress, &Rules::EUCLID)[4..], [ToolKind::Midpoint, ToolKind::Parallel]);
+        assert_eq!(tools(&levels, &progress, &Rules::RIGID)[4..], [ToolKind::RigidCompass, ToolKind::Midpoint, ToolKind::Parallel]);
+    }
+
+    #[test]
+    fn test_compass_only() {
+        let mut givens = Construction::new();
+        let a = givens.add(Definition::FreePoint(WorldPoint::new(-100.0, -20.0)));
+        let b = givens.add(Definition::FreePoint(WorldPoint::new(80.0, 40.0)));
+        let circles = |c: &mut Construction| {
+            let ca = c.add(Definition::Circle { center: a, through: b });
+            let cb = c.add(Definition::Circle { center: b, through: a });
+            (c.add(Definition::Intersection { a: ca, b: cb, index: 0 }), c.add(Definition::Intersection { a: ca, b: cb, index: 1 }))
+        };
+        let level = Level::new("Bisector", "", givens, Score { l: 2, e: 2 }, |c| {
+            let (top, bottom) = circles(c);
+            vec![c.add(Definition::Line { from: top, to: bottom })]
+        }).with_rules(Rules::COMPASS_ONLY);
+
+        // Two points on the goal line stand for it.
+        let mut c = level.start();
+        let (top, bottom) = circles(&mut c);
+        c.set_hidden(bottom, true);
+        assert!(!level.check(&c));
+        c.set_hidden(bottom, false);
+        assert!(level.check(&c));
+        assert!(level.check_exact(&c));
+
+        // The straightedge is not allowed.
+        c.add(Definition::Line { from: top, to: bottom });
+        assert!(!level.check(&c));
+
+        assert!(!tools(&levels(), &Progress::new(), &level.rules).contains(&ToolKind::Straightedge));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use json::{ object, JsonValue };
+
+use super::Score;
+
+/// The best scores of the player, by level name. The best L and E may come from different solutions.
+#[derive(Debug, Default)]
+pub struct Progress {
+    best: HashMap<String, Score>,
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn best(&self, level: &str) -> Option<Score> {
+        self.best.get(level).copied()
+    }
+
+    /// Keeps the better counts of `score`. Returns whether anything improved.
+    pub fn record(&mut self, level: &str, score: Score) -> bool {
+        match self.best.get_mut(level) {
+            Some(best) => {
+                let improved = score.l < best.l || score.e < best.e;
+                best.l = best.l.min(score.l);
+                best.e = best.e.min(score.e);
+                improved
+            },
+            None => {
+                self.best.insert(level.to_string(), score);
+                true
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut levels = JsonValue::new_object();
+        for (name, score) in &self.best {
+            levels[name.as_str()] = object!{ l: score.l, e: score.e };
+        }
+        object!{ version: 1, levels: levels }
+    }
+
+    pub fn from_json(data: &JsonValue) -> anyhow::Result<Self> {
+        if data["version"].as_u32() != Some(1) {
+            anyhow::bail!("Unsupported progress file version {}", data["version"]);
+        }
+
+        let mut best = HashMap::new();
+        for (name, score) in data["levels"].entries() {
+            match (score["l"].as_usize(), score["e"].as_usize()) {
+                (Some(l), Some(e)) => { best.insert(name.to_string(), Score { l, e }); },
+                _ => anyhow::bail!("Wrong score of `{}`", name),
+            }
+        }
+        Ok(Self { best })
+    }
+
+    /// The progress file in the configuration directory of the user.
+    pub fn default_path() -> anyhow::Result<std::path::PathBuf> {
+        Ok(crate::scene_system::document::config_dir()?.join("progress.json"))
+    }
+
+    /// Loads the progress at `path`, a missing file means no progress yet.
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&json::parse(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_json().pretty(4))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_progress {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut progress = Progress::new();
+        assert!(progress.record("Midpoint", Score { l: 4, e: 6 }));
+        assert!(!progress.record("Midpoint", Score { l: 5, e: 6 }));
+        assert!(progress.record("Midpoint", Score { l: 5, e: 5 }));
+        assert_eq!(progress.best("Midpoint"), Some(Score { l: 4, e: 5 }));
+        assert_eq!(progress.best("Other"), None);
+
+        let loaded = Progress::from_json(&json::parse(&progress.to_json().dump()).unwrap()).unwrap();
+        assert_eq!(loaded.best("Midpoint"), Some(Score { l: 4, e: 5 }));
+
+        assert!(Progress::from_json(&json::parse(r#"{ "version": 2, "levels": {} }"#).unwrap()).is_err());
+        assert!(Progress::from_json(&json::parse(r#"{ "version": 1, "levels": { "A": { "l": 1 } } }"#).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_files() {
+        let dir = std::env::temp_dir().join(format!("build-geometry-progress-{}", std::process::id()));
+        let path = dir.join("config").join("progress.json");
+        assert_eq!(Progress::load(&path).unwrap().best("Midpoint"), None);
+
+        let mut progress = Progress::new();
+        progress.record("Midpoint", Score { l: 4, e: 6 });
+        progress.save(&path).unwrap();
+        assert_eq!(Progress::load(&path).unwrap().best("Midpoint"), Some(Score { l: 4, e: 6 }));
+
+        // A file that can't be read isn't mistaken for a missing one.
+        std::fs::write(&path, "{ broken").unwrap();
+        assert!(Progress::load(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
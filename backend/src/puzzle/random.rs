@@ -0,0 +1,47 @@
+/// A small xorshift generator, good enough for perturbing constructions and reproducible from its seed.
+#[derive(Debug, Clone)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // Xorshift stays at zero forever.
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// A number in `[-range, range)`.
+    pub fn offset(&mut self, range: f32) -> f32 {
+        (self.next_f32() * 2.0 - 1.0) * range
+    }
+}
+
+#[cfg(test)]
+mod test_random {
+    use super::*;
+
+    #[test]
+    fn test_random() {
+        let mut a = Random::new(7);
+        let mut b = Random::new(7);
+        let values: Vec<_> = (0..1000).map(|_| a.next_f32()).collect();
+        assert!(values.iter().all(|v| (0.0..1.0).contains(v)));
+        assert_eq!(values[10], { (0..10).for_each(|_| { b.next_f32(); }); b.next_f32() });
+
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        assert!((mean - 0.5).abs() < 0.05);
+        assert_ne!(Random::new(0).next_u64(), 0);
+    }
+}
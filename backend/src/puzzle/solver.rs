@@ -0,0 +1,639 @@
+use std::collections::HashMap;
+
+use crate::data::{
+    prelude::*,
+    intersection::{ intersect, slots, distance_to },
+    rules::{ Rules, Compass },
+    fold::{ self, Fold, Axiom },
+    neusis,
+    exact::{ Tower, ExactGraph },
+};
+use super::GOAL_TOLERANCE;
+
+/// An object known to the search. The definitions of the steps refer to other nodes by index.
+struct Node {
+    graph: GraphType,
+    /// The object of the construction, or `None` for a step of the search.
+    id: Option<ObjectId>,
+    definition: Definition,
+}
+
+/// A curve rounded to find the sets of curves that may be the same, found in another order.
+type Key = (u8, i64, i64, i64);
+
+/// The exact graphs of the nodes, with the depth of the tower after each. `None` for the
+/// nodes not evaluated yet.
+type ExactNodes = (Tower, Vec<Option<(Option<ExactGraph>, usize)>>);
+
+/// Iterative deepening over the moves of the allowed instruments. Each move adds a line or
+/// a circle and all its new intersections with the curves before it.
+struct Search<'a> {
+    tolerance: Tolerance,
+    rules: Rules,
+    targets: &'a [GraphType],
+    nodes: Vec<Node>,
+    points: Vec<usize>,
+    curves: Vec<usize>,
+    /// Number of nodes taken from the construction.
+    existing: usize,
+    /// The sets of curves already explored, with the most moves left they were explored with.
+    /// The rounded keys only find the candidates, which are compared within the tolerance.
+    visited: HashMap<Vec<Key>, Vec<(Vec<GraphType>, usize)>>,
+    /// The number of states left to explore before giving up.
+    budget: usize,
+    /// In exact mode, the exact graphs of the nodes with the depth of the tower after each.
+    /// They are only evaluated to tell apart the points the tolerance can't.
+    exact: Option<ExactNodes>,
+}
+
+fn key(graph: &GraphType) -> Key {
+    let round = |v: f32| (v * 100.0).round() as i64;
+    match *graph {
+        GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to } => {
+            // The angle in [0, pi) and the signed distance from the origin. Rays and segments
+            // are only ever givens, which all sets of curves share.
+            let mut dir = (to - from).normalize();
+            if dir.y < 0.0 || (dir.y == 0.0 && dir.x < 0.0) {
+                dir = -dir;
+            }
+            let offset = dir.x * from.y - dir.y * from.x;
+            let kind = match graph {
+                GraphType::Ray { .. } => 3,
+                GraphType::Segment { .. } => 4,
+                _ => 0,
+            };
+            (kind, (dir.angle_from_x_axis().radians * 1e4).round() as i64, round(offset), 0)
+        },
+        GraphType::Circle { center, radius } => (1, round(center.x), round(center.y), round(radius)),
+        GraphType::Arc { center, radius, .. } => (5, round(center.x), round(center.y), round(radius)),
+        GraphType::Point(pos) => (2, round(pos.x), round(pos.y), 0),
+    }
+}
+
+impl<'a> Search<'a> {
+    fn new(construction: &Construction, targets: &'a [GraphType], rules: Rules) -> Self {
+        let mut search = Self {
+            tolerance: construction.tolerance,
+            rules,
+            targets,
+            nodes: Vec::new(),
+            points: Vec::new(),
+            curves: Vec::new(),
+            existing: 0,
+            visited: HashMap::new(),
+            budget: usize::MAX,
+            exact: construction.exact().map(|exact| (exact.tower.clone(), Vec::new())),
+        };
+        for (id, object) in construction.iter().filter(|(_, object)| !object.hidden) {
+            let Some(graph) = object.graph() else { continue };
+            match graph {
+                // Without anything drawing lines, they can't be intersected.
+                graph if graph.is_line() && !rules.draws_lines() => continue,
+                GraphType::Point(_) => search.points.push(search.nodes.len()),
+                _ => search.curves.push(search.nodes.len()),
+            }
+            search.nodes.push(Node { graph, id: Some(id), definition: object.definition.clone() });
+            if let (Some(exact), Some((tower, graphs))) = (construction.exact(), &mut search.exact) {
+                graphs.push(Some((exact.graph(id).cloned(), tower.depth())));
+            }
+        }
+        search.existing = search.nodes.len();
+        search
+    }
+
+    /// Whether `target` is a line only represented by two points on it.
+    fn by_points(&self, target: &GraphType) -> bool {
+        !self.rules.draws_lines() && target.is_line()
+    }
+
+    /// The nodes making up `target`, if they are all there.
+    fn find(&self, target: &GraphType) -> Option<Vec<usize>> {
+        if self.by_points(target) {
+            // The points are distinct already.
+            let on: Vec<_> = self.points.iter().copied()
+                .filter(|node| match self.nodes[*node].graph {
+                    GraphType::Point(pos) => distance_to(target, pos) <= GOAL_TOLERANCE.distance,
+                    _ => false,
+                })
+                .take(2)
+                .collect();
+            return (on.len() == 2).then_some(on);
+        }
+
+        let candidates = match target {
+            GraphType::Point(_) => &self.points,
+            _ => &self.curves,
+        };
+        candidates.iter().copied().find(|node| {
+            matches!(intersect(&self.nodes[*node].graph, target, &GOAL_TOLERANCE), Intersection::Coincident)
+        }).map(|node| vec![node])
+    }
+
+    /// The curves to add at the least, and whether points are missing too.
+    fn missing(&self) -> (Vec<&'a GraphType>, bool) {
+        let targets = self.targets;
+        let missing = targets.iter().filter(|target| self.find(target).is_none());
+        let (points, curves): (Vec<_>, Vec<_>) = missing
+            .partition(|target| matches!(target, GraphType::Point(_)) || self.by_points(target));
+        (curves, !points.is_empty())
+    }
+
+    fn evaluate(&self, definition: Definition) -> Option<(Definition, GraphType)> {
+        let graph = definition.evaluate(|id| Some(self.nodes[id.0].graph), &self.tolerance)?;
+        Some((definition, graph))
+    }
+
+    /// The exact graph of `node`, `None` if it is beyond square roots.
+    fn exact_graph(&mut self, node: usize) -> Option<ExactGraph> {
+        let (_, graphs) = self.exact.as_mut()?;
+        if graphs.len() <= node {
+            graphs.resize(node + 1, None);
+        }
+        if let Some((graph, _)) = &graphs[node] {
+            return graph.clone();
+        }
+        // The steps of the search refer to other nodes.
+        let definition = self.nodes[node].definition.clone();
+        for dependency in definition.dependencies() {
+            self.exact_graph(dependency.0);
+        }
+        let (tower, graphs) = self.exact.as_mut()?;
+        let graph = tower.evaluate(&definition, |id| graphs[id.0].as_ref().and_then(|(graph, _)| graph.clone()));
+        graphs[node] = Some((graph.clone(), tower.depth()));
+        graph
+    }
+
+    /// Whether the point nodes `a` and `b`, within the tolerance of each other, are the same.
+    /// Only exact mode tells them apart.
+    fn same_point(&mut self, a: usize, b: usize) -> bool {
+        if self.exact.is_none() {
+            return true;
+        }
+        match (self.exact_graph(a), self.exact_graph(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+
+    /// Adds a move and its new intersections. Returns the lengths to restore with [`Self::pop`],
+    /// or `None` if the move adds no new curve.
+    fn push(&mut self, definition: Definition, graph: GraphType) -> Option<(usize, usize, usize, usize)> {
+        // A line over a given segment or ray still adds the rest of it.
+        let same = |curve: &usize| {
+            let other = &self.nodes[*curve].graph;
+            !other.is_bounded() && matches!(intersect(other, &graph, &self.tolerance), Intersection::Coincident)
+        };
+        if self.curves.iter().any(same) {
+            return None;
+        }
+
+        let depth = self.exact.as_ref().map_or(0, |(tower, _)| tower.depth());
+        let saved = (self.nodes.len(), self.points.len(), self.curves.len(), depth);
+        let id = self.nodes.len();
+        self.nodes.push(Node { graph, id: None, definition });
+        for curve in self.curves.clone() {
+            let slots = slots(&self.nodes[curve].graph, &graph, &self.tolerance).unwrap_or_default();
+            for (index, pos) in slots.iter().enumerate().filter_map(|(index, pos)| Some((index, pos.as_ref()?))) {
+                let near: Vec<_> = self.points.iter().copied().filter(|point| match self.nodes[*point].graph {
+                    GraphType::Point(other) => (other - *pos).length() <= self.tolerance.distance,
+                    _ => false,
+                }).collect();
+                let node = self.nodes.len();
+                self.nodes.push(Node {
+                    graph: GraphType::Point(*pos),
+                    id: None,
+                    definition: Definition::Intersection { a: ObjectId(curve), b: ObjectId(id), index },
+                });
+                match near.into_iter().any(|point| self.same_point(point, node)) {
+                    true => self.pop_node(),
+                    false => self.points.push(node),
+                }
+            }
+        }
+        self.curves.push(id);
+        Some(saved)
+    }
+
+    fn pop_node(&mut self) {
+        self.nodes.pop();
+        if let Some((_, graphs)) = &mut self.exact {
+            graphs.truncate(self.nodes.len());
+        }
+    }
+
+    fn pop(&mut self, (nodes, points, curves, depth): (usize, usize, usize, usize)) {
+        self.nodes.truncate(nodes);
+        self.points.truncate(points);
+        self.curves.truncate(curves);
+        // The graphs evaluated since use the roots dropped with them.
+        if let Some((tower, graphs)) = &mut self.exact {
+            graphs.truncate(nodes);
+            graphs.iter_mut().filter(|graph| graph.as_ref().is_some_and(|(_, after)| *after > depth)).for_each(|graph| *graph = None);
+            tower.truncate(depth);
+        }
+    }
+
+    fn all_moves(&self) -> Vec<Definition> {
+        let mut moves = Vec::new();
+        for (i, a) in self.points.iter().enumerate() {
+            for (j, b) in self.points.iter().enumerate() {
+                let (a, b) = (ObjectId(*a), ObjectId(*b));
+                if i < j && self.rules.straightedge {
+                    moves.push(Definition::Line { from: a, to: b });
+                }
+                if i != j {
+                    moves.push(Definition::Circle { center: a, through: b });
+                }
+            }
+        }
+        if self.rules.compass == Compass::Rigid {
+            for center in &self.points {
+                for (i, from) in self.points.iter().enumerate() {
+                    for to in &self.points[i + 1..] {
+                        if ![from, to].contains(&center) {
+                            moves.push(Definition::CircleRadius { center: ObjectId(*center), from: ObjectId(*from), to: ObjectId(*to) });
+                        }
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    /// The moves drawing one of `curves` directly from the known points.
+    fn goal_moves(&self, curves: &[&GraphType]) -> Vec<Definition> {
+        let on = |graph: &GraphType| -> Vec<ObjectId> {
+            self.points.iter()
+                .filter(|point| match self.nodes[**point].graph {
+                    GraphType::Point(pos) => distance_to(graph, pos) <= GOAL_TOLERANCE.distance,
+                    _ => false,
+                })
+                .map(|point| ObjectId(*point))
+                .collect()
+        };
+
+        let mut moves = Vec::new();
+        for curve in curves {
+            // Drawing the whole line or circle makes a ray, a segment or an arc of it too.
+            match curve.carrier() {
+                GraphType::Line { .. } if self.rules.straightedge => {
+                    let points = on(curve);
+                    for (i, from) in points.iter().enumerate() {
+                        for to in &points[i + 1..] {
+                            moves.push(Definition::Line { from: *from, to: *to });
+                        }
+                    }
+                },
+                GraphType::Circle { center, radius } => {
+                    for center in on(&GraphType::Point(center)) {
+                        for through in on(curve) {
+                            moves.push(Definition::Circle { center, through });
+                        }
+                        if self.rules.compass == Compass::Rigid {
+                            moves.extend(self.distances(radius).map(|(from, to)| Definition::CircleRadius { center, from, to }));
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+        moves
+    }
+
+    /// The moves of the marked ruler and the folds, with their graphs. With `goals`, only the moves
+    /// drawing one of them.
+    fn instrument_moves(&self, goals: Option<&[&GraphType]>) -> Vec<(Definition, GraphType)> {
+        let graph = |node: usize| self.nodes[node].graph;
+        let drawn = |line: &GraphType| goals.is_none_or(|goals| goals.iter().any(|goal| {
+            matches!(intersect(line, goal, &GOAL_TOLERANCE), Intersection::Coincident)
+        }));
+        let mut moves = Vec::new();
+
+        if self.rules.neusis {
+            // The ruler turns around its pole, so only the points on a goal are poles for it.
+            let poles: Vec<_> = self.points.iter().copied().filter(|pole| match (goals, graph(*pole)) {
+                (Some(goals), GraphType::Point(pos)) => goals.iter().any(|goal| {
+                    goal.is_line() && distance_to(goal, pos) <= GOAL_TOLERANCE.distance
+                }),
+                _ => true,
+            }).collect();
+            for (i, from) in self.points.iter().enumerate() {
+                for to in &self.points[i + 1..] {
+                    let length = match (graph(*from), graph(*to)) {
+                        (GraphType::Point(from), GraphType::Point(to)) => (to - from).length(),
+                        _ => continue,
+                    };
+                    for pole in &poles {
+                        let GraphType::Point(pos) = graph(*pole) else { continue };
+                        for (j, a) in self.curves.iter().enumerate() {
+                            for b in &self.curves[j..] {
+                                let lines = neusis::lines(length, pos, &graph(*a), &graph(*b), &self.tolerance);
+                                moves.extend(lines.into_iter().enumerate().filter(|(_, line)| drawn(line)).map(|(index, line)| {
+                                    let [from, to, pole, a, b] = [*from, *to, *pole, *a, *b].map(ObjectId);
+                                    (Definition::Neusis { from, to, pole, a, b, index }, line)
+                                }));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.rules.origami {
+            let lines: Vec<_> = self.curves.iter().copied().filter(|curve| graph(*curve).is_line()).collect();
+            for axiom in Axiom::ALL {
+                // Every choice of distinct inputs of the right kinds.
+                let mut choices = vec![Vec::new()];
+                for kind in axiom.inputs() {
+                    let candidates = if *kind == ObjectKind::Point { &self.points } else { &lines };
+                    choices = choices.iter().flat_map(|choice: &Vec<usize>| {
+                        candidates.iter().filter(|node| !choice.contains(node)).map(|node| [&choice[..], &[*node]].concat())
+                    }).collect();
+                }
+                for choice in choices {
+                    let graphs: Vec<_> = choice.iter().map(|node| graph(*node)).collect();
+                    let inputs: Vec<_> = choice.into_iter().map(ObjectId).collect();
+                    for (index, crease) in fold::folds(axiom, &graphs, &self.tolerance).into_iter().enumerate() {
+                        if drawn(&crease) {
+                            moves.push((Definition::Fold(Fold { axiom, inputs: inputs.clone(), index }), crease));
+                        }
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    /// The pairs of known points `radius` apart.
+    fn distances(&self, radius: f32) -> impl Iterator<Item = (ObjectId, ObjectId)> + '_ {
+        let pos = |node: usize| match self.nodes[node].graph {
+            GraphType::Point(pos) => pos,
+            _ => unreachable!(),
+        };
+        self.points.iter().enumerate().flat_map(move |(i, from)| {
+            self.points[i + 1..].iter()
+                .filter(move |to| ((pos(**to) - pos(*from)).length() - radius).abs() <= GOAL_TOLERANCE.distance)
+                .map(move |to| (ObjectId(*from), ObjectId(*to)))
+        })
+    }
+
+    /// Whether the curves added so far were already explored with at least `remaining` moves left.
+    /// Otherwise records them.
+    fn explored(&mut self, remaining: usize) -> bool {
+        let mut state: Vec<_> = self.curves.iter()
+            .filter(|curve| **curve >= self.existing)
+            .map(|curve| (key(&self.nodes[*curve].graph), self.nodes[*curve].graph))
+            .collect();
+        state.sort_unstable_by_key(|(key, _)| *key);
+        let (keys, graphs): (Vec<_>, Vec<_>) = state.into_iter().unzip();
+
+        let same = |other: &[GraphType]| other.iter().zip(&graphs).all(|(a, b)| {
+            matches!(intersect(a, b, &self.tolerance), Intersection::Coincident)
+        });
+        let candidates = self.visited.entry(keys).or_default();
+        match candidates.iter_mut().find(|(other, _)| same(other)) {
+            Some((_, visited)) if *visited >= remaining => return true,
+            Some((_, visited)) => *visited = remaining,
+            None => candidates.push((graphs, remaining)),
+        }
+        false
+    }
+
+    fn search(&mut self, remaining: usize) -> bool {
+        if self.budget == 0 {
+            return false;
+        }
+        self.budget -= 1;
+
+        let (curves, points) = self.missing();
+        if curves.is_empty() && !points {
+            return true;
+        }
+        if curves.len().max(points as usize) > remaining {
+            return false;
+        }
+
+        if self.explored(remaining) {
+            return false;
+        }
+
+        // With as many moves left as curves to draw, every move has to draw one of them.
+        let goals = (curves.len() == remaining).then_some(&curves[..]);
+        let moves = match goals {
+            Some(goals) => self.goal_moves(goals),
+            None => self.all_moves(),
+        };
+        let mut moves: Vec<_> = moves.into_iter().filter_map(|definition| self.evaluate(definition)).collect();
+        moves.extend(self.instrument_moves(goals));
+        for (definition, graph) in moves {
+            let Some(saved) = self.push(definition, graph) else { continue };
+            if self.search(remaining - 1) {
+                return true;
+            }
+            self.pop(saved);
+        }
+        false
+    }
+
+    /// The steps leading to the targets, with the ids they get when added to `construction` in order.
+    fn steps(&self, construction: &Construction) -> Vec<Definition> {
+        let mut needed = vec![false; self.nodes.len()];
+        let mut stack: Vec<_> = self.targets.iter().filter_map(|target| self.find(target))
+            .flatten()
+            .chain(self.curves.iter().copied().filter(|curve| *curve >= self.existing))
+            .collect();
+        while let Some(node) = stack.pop() {
+            if node >= self.existing && !needed[node] {
+                needed[node] = true;
+                stack.extend(self.nodes[node].definition.dependencies().iter().map(|id| id.0));
+            }
+        }
+
+        let mut ids = HashMap::new();
+        let mut steps = Vec::new();
+        for (node, _) in needed.iter().enumerate().filter(|(_, needed)| **needed) {
+            let definition = self.nodes[node].definition.map_ids(|id| match self.nodes[id.0].id {
+                Some(id) => id,
+                None => ids[&id.0],
+            });
+            ids.insert(node, ObjectId(construction.next_id().0 + steps.len()));
+            steps.push(definition);
+        }
+        steps
+    }
+}
+
+/// Finds a construction of `targets` from the visible objects of `construction` with the fewest
+/// lines and circles allowed by `rules`, up to `max_moves`. Returns the definitions to add in order.
+pub fn solve(construction: &Construction, targets: &[GraphType], max_moves: usize, rules: &Rules) -> Option<Vec<Definition>> {
+    solve_within(construction, targets, max_moves, rules, usize::MAX).ok()?
+}
+
+/// Like [`solve`], but gives up with an error after exploring `budget` states.
+pub fn solve_within(
+    construction: &Construction,
+    targets: &[GraphType],
+    max_moves: usize,
+    rules: &Rules,
+    budget: usize,
+) -> anyhow::Result<Option<Vec<Definition>>> {
+    let mut search = Search::new(construction, targets, *rules);
+    search.budget = budget;
+    let found = (0..=max_moves).find(|moves| search.search(*moves));
+    match found {
+        Some(_) => Ok(Some(search.steps(construction))),
+        None if search.budget == 0 => anyhow::bail!("Gave up after exploring {} states", budget),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test_solver {
+    use super::*;
+
+    fn moves(steps: &[Definition]) -> usize {
+        steps.iter().filter(|step| step.kind() != ObjectKind::Point).count()
+    }
+
+    #[test]
+    fn test_exact_points() {
+        for exact in [false, true] {
+            let mut c = Construction::new();
+            let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+            let b = c.add(Definition::FreePoint(p(100.0, 0.0)));
+            // Closer to the circle than the tolerance.
+            let near = c.add(Definition::FreePoint(p(0.0, 100.0005)));
+            c.add(Definition::Circle { center: a, through: b });
+            c.set_exact(exact);
+            c.update();
+
+            let mut search = Search::new(&c, &[], Rules::EUCLID);
+            let (definition, graph) = search.evaluate(Definition::Line { from: a, to: near }).unwrap();
+            let saved = search.push(definition, graph).unwrap();
+            // The top of the circle is only a new point when told apart exactly.
+            assert_eq!(search.points.len(), if exact { 5 } else { 4 });
+            search.pop(saved);
+            assert_eq!(search.points.len(), 3);
+            if let Some((tower, _)) = &search.exact {
+                assert_eq!(tower.depth(), saved.3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_explored() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(50.0, 0.0)));
+        // Both circles round to the same key, but they are further apart than the tolerance.
+        let near = c.add(Definition::FreePoint(p(50.004, 0.0)));
+        c.update();
+
+        let mut search = Search::new(&c, &[], Rules::EUCLID);
+        assert!(!search.explored(2));
+        for (through, seen) in [(b, false), (b, true), (near, false)] {
+            let (definition, graph) = search.evaluate(Definition::Circle { center: a, through }).unwrap();
+            assert_eq!(key(&graph), (1, 0, 0, 5000));
+            let saved = search.push(definition, graph).unwrap();
+            assert_eq!(search.explored(2), seen);
+            assert!(search.explored(1));
+            search.pop(saved);
+        }
+        // More moves left than before explore it again.
+        assert!(!search.explored(3));
+        assert!(search.explored(3));
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut c = Construction::new();
+        c.add(Definition::FreePoint(p(-100.0, 0.0)));
+        c.add(Definition::FreePoint(p(100.0, 0.0)));
+        let bisector = GraphType::Line { from: p(0.0, -10.0), to: p(0.0, 10.0) };
+
+        assert_eq!(solve(&c, &[bisector], 2, &Rules::EUCLID), None);
+        assert!(solve_within(&c, &[bisector], 4, &Rules::EUCLID, 5).is_err());
+        let steps = solve(&c, &[bisector], 4, &Rules::EUCLID).unwrap();
+        assert_eq!(moves(&steps), 3);
+
+        // The steps build the target.
+        let next = c.next_id().0;
+        for step in steps {
+            c.add(step);
+        }
+        c.update();
+        let last = ObjectId(c.next_id().0 - 1);
+        assert!(matches!(intersect(&c.graph(last).unwrap(), &bisector, &GOAL_TOLERANCE), Intersection::Coincident));
+        assert!(c.next_id().0 > next);
+
+        // Something already there takes no moves.
+        assert_eq!(solve(&c, &[GraphType::Point(p(100.0, 0.0))], 0, &Rules::EUCLID), Some(vec![]));
+    }
+
+    #[test]
+    fn test_point() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(100.0, 0.0)));
+        c.add(Definition::Line { from: a, to: b });
+
+        // The reflection of A about B: one circle.
+        let steps = solve(&c, &[GraphType::Point(p(200.0, 0.0))], 3, &Rules::EUCLID).unwrap();
+        assert_eq!(moves(&steps), 1);
+        assert_eq!(steps.len(), 2);
+    }
+
+    #[test]
+    fn test_compass_only() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(-100.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(100.0, 0.0)));
+        c.add(Definition::Line { from: a, to: b });
+        let bisector = GraphType::Line { from: p(0.0, -10.0), to: p(0.0, 10.0) };
+
+        // Two points on the bisector are enough, and the given line can't be used.
+        assert_eq!(solve(&c, &[bisector], 1, &Rules::COMPASS_ONLY), None);
+        let steps = solve(&c, &[bisector], 3, &Rules::COMPASS_ONLY).unwrap();
+        assert_eq!(moves(&steps), 2);
+        assert!(steps.iter().all(|step| Rules::COMPASS_ONLY.allows(step, &c) && step.kind() != ObjectKind::Line));
+        assert_eq!(solve(&c, &[GraphType::Point(p(0.0, 0.0))], 2, &Rules::COMPASS_ONLY), None);
+    }
+
+    #[test]
+    fn test_neusis() {
+        let mut c = Construction::new();
+        let pole = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(0.0, 10.0)));
+        let b = c.add(Definition::FreePoint(p(100.0, 10.0)));
+        c.add(Definition::Line { from: a, to: b });
+        let a = c.add(Definition::FreePoint(p(0.0, 20.0)));
+        let b = c.add(Definition::FreePoint(p(100.0, 20.0)));
+        c.add(Definition::Line { from: a, to: b });
+        // The marks, 20 apart.
+        c.add(Definition::FreePoint(p(-50.0, -50.0)));
+        c.add(Definition::FreePoint(p(-30.0, -50.0)));
+        c.update();
+        // Between the lines 10 apart, the marks fit on the line through the pole at 30 degrees.
+        let target = GraphType::Line { from: p(0.0, 0.0), to: p(3f32.sqrt() * 10.0, 10.0) };
+
+        // The ruler also draws the goal as the last move.
+        assert_eq!(solve(&c, &[target], 1, &Rules::EUCLID), None);
+        let steps = solve(&c, &[target], 1, &Rules::NEUSIS).unwrap();
+        assert_eq!(moves(&steps), 1);
+        assert!(matches!(steps[0], Definition::Neusis { pole: p, .. } if p == pole));
+    }
+
+    #[test]
+    fn test_rigid() {
+        let mut c = Construction::new();
+        c.add(Definition::FreePoint(p(0.0, 0.0)));
+        c.add(Definition::FreePoint(p(30.0, 40.0)));
+        c.add(Definition::FreePoint(p(200.0, 0.0)));
+        let target = GraphType::Circle { center: p(200.0, 0.0), radius: 50.0 };
+
+        // Transferring a distance takes one move, which the collapsing compass can't do.
+        assert_eq!(solve(&c, &[target], 1, &Rules::EUCLID), None);
+        let steps = solve(&c, &[target], 1, &Rules::RIGID).unwrap();
+        assert_eq!(steps, vec![Definition::CircleRadius { center: ObjectId(2), from: ObjectId(0), to: ObjectId(1) }]);
+    }
+}
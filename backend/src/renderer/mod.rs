@@ -4,12 +4,10 @@ use crate::{
     scene_system::Scene,
 };
 
-use wgpu::util::DeviceExt;
-
 const DEFAULT_VIEW_SIZE: WorldSize = WorldSize::new(1000.0, 1000.0);
 
 pub trait Renderer {
-    fn update_view(&self, view_data: &ViewData, state: &State) {
+    fn update_view(&mut self, view_data: &ViewData, state: &State) {
         self.update_view_matrix(&ProjMatrix::look_to(view_data), state)
     }
 
@@ -26,7 +24,7 @@ pub trait Renderer {
 
     fn init_in_scene(&mut self, scene: &Scene, state: &State);
 
-    fn render(&mut self, state: &State, output: &wgpu::SurfaceTexture);
+    fn render(&mut self, state: &State, output: &wgpu::Texture);
 }
 
 use lyon::tessellation::{
@@ -40,8 +38,164 @@ use lyon::tessellation::{
     BuffersBuilder,
 };
 
-type DefaultVertexBuffers = VertexBuffers<[f32; 2], u16>;
-type GraphMeshStack = Vec<(wgpu::Buffer, wgpu::Buffer, usize)>;
+/// The shader moves each vertex by `normal * width / 2` screen pixels, so that strokes
+/// keep their on-screen thickness at every zoom.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    normal: [f32; 2],
+    width: f32,
+    color: [u8; 4],
+}
+
+impl Vertex {
+    fn fill(vertex: FillVertex, color: [u8; 4]) -> Self {
+        Self { position: vertex.position().to_array(), normal: [0.0; 2], width: 0.0, color }
+    }
+
+    fn stroke(vertex: StrokeVertex, width: f32, color: [u8; 4]) -> Self {
+        Self { position: vertex.position_on_path().to_array(), normal: vertex.normal().to_array(), width, color }
+    }
+}
+
+type DefaultVertexBuffers = VertexBuffers<Vertex, u32>;
+
+/// Where a graph lies in its batch. The slot is larger than the graph, so that
+/// it can change a bit without moving the others.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    vertex_start: usize,
+    vertex_capacity: usize,
+    index_start: usize,
+    index_capacity: usize,
+}
+
+impl Slot {
+    fn fits(&self, geometry: &DefaultVertexBuffers) -> bool {
+        geometry.vertices.len() <= self.vertex_capacity && geometry.indices.len() <= self.index_capacity
+    }
+
+    /// The indices of `geometry` in the batch, padded with empty triangles to the slot size.
+    fn indices<'a>(&self, geometry: &'a DefaultVertexBuffers) -> impl Iterator<Item = u32> + 'a {
+        let (start, capacity) = (self.vertex_start as u32, self.index_capacity);
+        geometry.indices.iter()
+            .map(move |index| index + start)
+            .chain(std::iter::repeat(start))
+            .take(capacity)
+    }
+}
+
+/// Geometry of many graphs in one vertex buffer and one index buffer, drawn in one call.
+struct Batch {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    vertex_capacity: usize,
+    index_capacity: usize,
+    num_indices: usize,
+    // Set when a graph was added, removed or outgrew its slot, and the slots have to be laid out again.
+    repack: bool,
+}
+
+impl Batch {
+    fn new(state: &State) -> Self {
+        let (vertex_buffer, index_buffer) = Self::create_buffers(1024, 4096, state);
+        Self {
+            vertex_buffer,
+            index_buffer,
+            vertex_capacity: 1024,
+            index_capacity: 4096,
+            num_indices: 0,
+            repack: true,
+        }
+    }
+
+    fn create_buffers(vertex_capacity: usize, index_capacity: usize, state: &State) -> (wgpu::Buffer, wgpu::Buffer) {
+        let create_buffer = |label, size, usage| state.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: size as wgpu::BufferAddress,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        (
+            create_buffer("Vertex Buffer", vertex_capacity * std::mem::size_of::<Vertex>(), wgpu::BufferUsages::VERTEX),
+            create_buffer("Index Buffer", index_capacity * std::mem::size_of::<u32>(), wgpu::BufferUsages::INDEX),
+        )
+    }
+
+    /// Writes `geometry` in place.
+    fn write(&self, slot: &Slot, geometry: &DefaultVertexBuffers, state: &State) {
+        let indices: Vec<u32> = slot.indices(geometry).collect();
+        state.queue.write_buffer(
+            &self.vertex_buffer,
+            (slot.vertex_start * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&geometry.vertices),
+        );
+        state.queue.write_buffer(
+            &self.index_buffer,
+            (slot.index_start * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            bytemuck::cast_slice(&indices),
+        );
+    }
+
+    /// Lays out all the graphs again, with `slack` extra room in each slot, and uploads them.
+    fn pack<'a, I: Iterator<Item = &'a DefaultVertexBuffers>>(&mut self, geometries: I, slack: f32, state: &State) -> Vec<Slot> {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let slots = geometries.map(|geometry| {
+            let extra = |len: usize| len + (len as f32 * slack) as usize;
+            let slot = Slot {
+                vertex_start: vertices.len(),
+                vertex_capacity: extra(geometry.vertices.len()),
+                index_start: indices.len(),
+                // Whole triangles only.
+                index_capacity: extra(geometry.indices.len()).div_ceil(3) * 3,
+            };
+            vertices.extend_from_slice(&geometry.vertices);
+            vertices.resize(slot.vertex_start + slot.vertex_capacity, bytemuck::Zeroable::zeroed());
+            indices.extend(slot.indices(geometry));
+            slot
+        }).collect();
+
+        if vertices.len() > self.vertex_capacity || indices.len() > self.index_capacity {
+            self.vertex_capacity = self.vertex_capacity.max(vertices.len().next_power_of_two());
+            self.index_capacity = self.index_capacity.max(indices.len().next_power_of_two());
+            (self.vertex_buffer, self.index_buffer) = Self::create_buffers(self.vertex_capacity, self.index_capacity, state);
+        }
+        state.queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        state.queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(&indices));
+        self.num_indices = indices.len();
+        self.repack = false;
+        slots
+    }
+
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.num_indices > 0 {
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.num_indices as u32, 0, 0..1);
+        }
+    }
+}
+
+/// What a construction object was last tessellated from.
+struct CachedObject {
+    graph: GraphType,
+    style: Style,
+    geometry: DefaultVertexBuffers,
+    slot: Option<Slot>,
+}
+
+impl CachedObject {
+    /// Lines are clipped to the view and dashes are measured in pixels.
+    fn on_view(&self) -> bool {
+        self.graph.is_line() || self.style.dash_pattern().is_some()
+    }
+
+    fn is_point(&self) -> bool {
+        matches!(self.graph, GraphType::Point(_))
+    }
+}
 
 pub struct DefaultRenderer {
     render_pipeline: wgpu::RenderPipeline,
@@ -51,11 +205,24 @@ pub struct DefaultRenderer {
     effects_buffer_bytes_per_row: u32,
 
     proj_buffer: wgpu::Buffer,
-    color_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
 
-    graphs: GraphMeshStack,
-    tips: GraphMeshStack,
+    // Indexed by object id.
+    objects: Vec<Option<CachedObject>>,
+    // Points are drawn over the curves, and the tips over everything.
+    curves: Batch,
+    points: Batch,
+    tips: Batch,
+    // Number of graphs tessellated by the last update.
+    tessellated: usize,
+
+    view_rect: WorldRect,
+    pixel_size: f32,
+    // The pixel size the graphs were flattened for.
+    graphs_pixel_size: f32,
+    // Set when the view changed and the lines need to be clipped again.
+    view_changed: bool,
+    scene_revision: Option<u64>,
 }
 
 impl Renderer for DefaultRenderer {
@@ -64,7 +231,8 @@ impl Renderer for DefaultRenderer {
         self.update_view_matrix(
             &ProjMatrix::look_to_range(view_data, DEFAULT_VIEW_SIZE),
             state
-        )
+        );
+        self.update_pixel_size(view_data, state);
     }
 
     fn update_view_matrix(&self, view_mat: &ProjMatrix, state: &State) {
@@ -75,62 +243,89 @@ impl Renderer for DefaultRenderer {
         );
     }
 
+    fn update_view(&mut self, view_data: &ViewData, state: &State) {
+        self.update_view_matrix(&ProjMatrix::look_to(view_data), state);
+        self.update_pixel_size(view_data, state);
+    }
+
     fn init_in_scene(&mut self, scene: &Scene, state: &State) {
-        let mut file_tessellator = FillTessellator::new();
-        let mut file_options = FillOptions::default();
-
-        let mut stroke_tessellator = StrokeTessellator::new();
-        let mut stroke_options = StrokeOptions::default();
-        stroke_options.line_width = 10.0;
-
-        self.graphs = scene.graph.iter().map(|graph| {
-            let mut output = DefaultVertexBuffers::new();
-            match graph {
-                GraphType::Circle { center, radius } => {
-                    let mut builder = BuffersBuilder::new(
-                        &mut output, |vertex: StrokeVertex| {
-                            vertex.position().to_array()
-                        }
-                    );
-            
-                    stroke_tessellator.tessellate_circle(
-                        center.cast_unit(), 
-                        *radius, &stroke_options, 
-                        &mut builder
-                    ).expect("Failed tessellation graph stroke!");
-
-                    let vertex_buffer = state.device.create_buffer_init(
-                        &wgpu::util::BufferInitDescriptor {
-                            label: Some("Vertex Buffer"),
-                            contents: bytemuck::cast_slice(&output.vertices),
-                            usage: wgpu::BufferUsages::VERTEX,
-                        }
-                    );
-                    
-                    let index_buffer = state.device.create_buffer_init(
-                        &wgpu::util::BufferInitDescriptor {
-                            label: Some("Index Buffer"),
-                            contents: bytemuck::cast_slice(&output.indices),
-                            usage: wgpu::BufferUsages::INDEX,
-                        }
-                    );
-            
-                    (vertex_buffer, index_buffer, output.indices.len())
-                }
-                _ => panic!("undefine Graph!")
-            }
-        }).collect();
+        self.objects.clear();
+        self.curves.repack = true;
+        self.points.repack = true;
+        self.scene_revision = None;
+        self.update_scene(scene, state);
     }
 
+    /// Re-tessellates the objects that were added or changed since the last update, and
+    /// those depending on the view when it moved.
     fn update_scene(&mut self, scene: &Scene, state: &State) {
-        ;
+        self.tessellated = 0;
+
+        // Curves are flattened finely enough for zooming in up to twice.
+        let zoom = self.pixel_size / self.graphs_pixel_size;
+        let rebuild = !(0.5..=2.0).contains(&zoom);
+        if rebuild {
+            self.graphs_pixel_size = self.pixel_size;
+        }
+
+        let revision = scene.construction.revision();
+        if self.scene_revision != Some(revision) || self.view_changed || rebuild {
+            let construction = &scene.construction;
+            self.objects.resize_with(construction.next_id().0, || None);
+
+            for index in 0..self.objects.len() {
+                let cached = self.objects[index].take();
+                let current = construction.get(ObjectId(index))
+                    .filter(|object| !object.hidden)
+                    .and_then(|object| object.graph().map(|graph| (graph, object.style)));
+                let Some((graph, style)) = current else {
+                    if let Some(cached) = cached {
+                        self.batch(&cached).repack = true;
+                    }
+                    continue
+                };
+
+                let changed = cached.as_ref().is_none_or(|cached| cached.graph != graph || cached.style != style);
+                let mut cached = match cached {
+                    Some(cached) => CachedObject { graph, style, ..cached },
+                    None => CachedObject { graph, style, geometry: DefaultVertexBuffers::new(), slot: None },
+                };
+                if changed || rebuild || (self.view_changed && cached.on_view()) {
+                    cached.geometry = self.tessellate(graph, &style);
+                    self.tessellated += 1;
+
+                    let batch = self.batch(&cached);
+                    match cached.slot {
+                        Some(slot) if !batch.repack && slot.fits(&cached.geometry) => batch.write(&slot, &cached.geometry, state),
+                        _ => batch.repack = true,
+                    }
+                }
+                self.objects[index] = Some(cached);
+            }
+
+            for is_point in [false, true] {
+                let batch = if is_point { &mut self.points } else { &mut self.curves };
+                if batch.repack {
+                    let mut objects: Vec<_> = self.objects.iter_mut().flatten().filter(|cached| cached.is_point() == is_point).collect();
+                    let slots = batch.pack(objects.iter().map(|cached| &cached.geometry), 0.25, state);
+                    objects.iter_mut().zip(slots).for_each(|(cached, slot)| cached.slot = Some(slot));
+                }
+            }
+
+            self.scene_revision = Some(revision);
+            self.view_changed = false;
+        }
+
+        let tip_style = Style::default();
+        let tips: Vec<_> = scene.tip.iter().map(|graph| self.tessellate(*graph, &tip_style)).collect();
+        self.tips.pack(tips.iter(), 0.0, state);
     }
 
-    fn render(&mut self, state: &State, output: &wgpu::SurfaceTexture) {
+    fn render(&mut self, state: &State, output: &wgpu::Texture) {
         let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Game Render Encoder"),
         });
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let view = output.create_view(&wgpu::TextureViewDescriptor::default());
 
         // Render pass
         {
@@ -154,14 +349,11 @@ impl Renderer for DefaultRenderer {
 
             render_pass.set_pipeline(&self.render_pipeline);
 
-            self.graphs.iter().for_each(|(vertex_buffer, index_buffer, num)| {
-                state.queue.write_buffer(&self.color_buffer, 0, bytemuck::cast_slice(&[1.0f32, 0.0f32, 0.0f32, 1.0f32]));
-                render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
 
-                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-                render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                render_pass.draw_indexed(0..*num as u32, 0, 0..1);
-            });
+            self.curves.draw(&mut render_pass);
+            self.points.draw(&mut render_pass);
+            self.tips.draw(&mut render_pass);
         }
 
         // Compute pass
@@ -203,7 +395,7 @@ impl Renderer for DefaultRenderer {
                 }
             },
             wgpu::ImageCopyTexture {
-                texture: &output.texture,
+                texture: output,
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
@@ -216,11 +408,159 @@ impl Renderer for DefaultRenderer {
 }
 
 impl DefaultRenderer {
+    fn batch(&mut self, cached: &CachedObject) -> &mut Batch {
+        if cached.is_point() { &mut self.points } else { &mut self.curves }
+    }
+
+    /// Flattening tolerance in screen pixels.
+    const TOLERANCE: f32 = 0.1;
+
+    fn update_pixel_size(&mut self, view_data: &ViewData, state: &State) {
+        state.queue.write_buffer(
+            &self.proj_buffer,
+            std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+            bytemuck::cast_slice(&[view_data.pixel_size, 0.0, 0.0, 0.0]),
+        );
+        self.pixel_size = view_data.pixel_size;
+        self.view_rect = view_data.world_rect();
+        self.view_changed = true;
+    }
+
+    /// Tessellates the stroke of `path`, given in world units, with the dashes and width in screen pixels.
+    fn tessellate_stroke(&self, path: &Path, style: &Style, output: &mut DefaultVertexBuffers) {
+        let (width, color) = (style.width, style.stroke.to_array());
+        let tolerance = Self::TOLERANCE * self.pixel_size;
+        let path = match style.dash_pattern() {
+            Some(pattern) => dash_path(path, pattern.map(|length| length * self.pixel_size), tolerance),
+            None => path.clone(),
+        };
+        StrokeTessellator::new().tessellate_path(
+            &path,
+            &StrokeOptions::default().with_tolerance(tolerance),
+            &mut BuffersBuilder::new(output, |vertex: StrokeVertex| Vertex::stroke(vertex, width, color))
+        ).expect("Failed tessellation graph stroke!");
+    }
+
+    fn tessellate_circle(&self, center: WorldPoint, radius: f32, style: &Style) -> DefaultVertexBuffers {
+        let mut output = DefaultVertexBuffers::new();
+        if let Some(fill) = style.fill {
+            let color = fill.to_array();
+            FillTessellator::new().tessellate_circle(
+                center.cast_unit(),
+                radius,
+                &FillOptions::default().with_tolerance(Self::TOLERANCE * self.pixel_size),
+                &mut BuffersBuilder::new(&mut output, |vertex: FillVertex| Vertex::fill(vertex, color))
+            ).expect("Failed tessellation graph fill!");
+        }
+
+        let mut builder = Path::builder();
+        builder.add_circle(center.cast_unit(), radius, lyon::path::Winding::Positive);
+        self.tessellate_stroke(&builder.build(), style, &mut output);
+        output
+    }
+
+    /// The arc from `start` counterclockwise to `end`, closed into `region` when it is given.
+    fn arc_path(center: WorldPoint, radius: f32, start: f32, end: f32, region: Option<ArcRegion>) -> Path {
+        let arc = lyon::geom::Arc {
+            center: center.cast_unit(),
+            radii: lyon::math::vector(radius, radius),
+            start_angle: lyon::math::Angle::radians(start),
+            sweep_angle: lyon::math::Angle::radians(end - start),
+            x_rotation: lyon::math::Angle::zero(),
+        };
+
+        let mut builder = Path::builder();
+        if let Some(ArcRegion::Sector) = region {
+            builder.begin(center.cast_unit());
+            builder.line_to(arc.from());
+        } else {
+            builder.begin(arc.from());
+        }
+        arc.for_each_quadratic_bezier(&mut |curve| {
+            builder.quadratic_bezier_to(curve.ctrl, curve.to);
+        });
+        builder.end(region.is_some());
+        builder.build()
+    }
+
+    fn tessellate_arc(&self, center: WorldPoint, radius: f32, start: f32, end: f32, region: ArcRegion, style: &Style) -> DefaultVertexBuffers {
+        let mut output = DefaultVertexBuffers::new();
+        if let Some(fill) = style.fill {
+            let color = fill.to_array();
+            FillTessellator::new().tessellate_path(
+                &Self::arc_path(center, radius, start, end, Some(region)),
+                &FillOptions::default().with_tolerance(Self::TOLERANCE * self.pixel_size),
+                &mut BuffersBuilder::new(&mut output, |vertex: FillVertex| Vertex::fill(vertex, color))
+            ).expect("Failed tessellation graph fill!");
+        }
+
+        self.tessellate_stroke(&Self::arc_path(center, radius, start, end, None), style, &mut output);
+        output
+    }
+
+    /// `offset` is how far `from` lies along the unclipped line, so that the dashes stay in place.
+    fn tessellate_line(&self, from: WorldPoint, to: WorldPoint, offset: f32, style: &Style) -> DefaultVertexBuffers {
+        let mut output = DefaultVertexBuffers::new();
+        // Start a dashed line at the beginning of its current period.
+        let start = match style.dash_pattern() {
+            Some([dash, gap]) => from - (to - from).normalize() * offset.rem_euclid((dash + gap) * self.pixel_size),
+            None => from,
+        };
+
+        let mut builder = Path::builder();
+        builder.begin(start.cast_unit());
+        builder.line_to(to.cast_unit());
+        builder.end(false);
+
+        self.tessellate_stroke(&builder.build(), style, &mut output);
+        output
+    }
+
+    /// Points are discs of a fixed size on screen.
+    fn tessellate_point(pos: WorldPoint, style: &Style) -> DefaultVertexBuffers {
+        let mut output = DefaultVertexBuffers::new();
+        let (diameter, color) = (style.point_radius() * 2.0, style.stroke.to_array());
+        FillTessellator::new().tessellate_circle(
+            lyon::math::point(0.0, 0.0),
+            1.0,
+            &FillOptions::default().with_tolerance(Self::TOLERANCE / style.point_radius().max(1.0)),
+            &mut BuffersBuilder::new(&mut output, |vertex: FillVertex| {
+                Vertex { position: pos.to_array(), normal: vertex.position().to_array(), width: diameter, color }
+            })
+        ).expect("Failed tessellation graph fill!");
+        output
+    }
+
+    fn tessellate(&self, graph: GraphType, style: &Style) -> DefaultVertexBuffers {
+        match graph {
+            GraphType::Circle { center, radius } => self.tessellate_circle(center, radius, style),
+            GraphType::Arc { center, radius, start, end, region } => self.tessellate_arc(center, radius, start, end, region, style),
+            GraphType::Point(pos) => Self::tessellate_point(pos, style),
+            GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to } => {
+                // Extend the rect so that the infinite ends stay out of sight.
+                let margin = style.width * self.pixel_size;
+                let rect = self.view_rect.inflate(margin, margin);
+                match graph.clip(&rect).filter(|(from, to)| from != to) {
+                    Some((clipped_from, clipped_to)) => {
+                        let offset = (clipped_from - from).dot((to - from).normalize());
+                        self.tessellate_line(clipped_from, clipped_to, offset, style)
+                    },
+                    None => DefaultVertexBuffers::new(),
+                }
+            },
+        }
+    }
+
     fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
-        static ATTRIBS: [wgpu::VertexAttribute; 1]  = wgpu::vertex_attr_array![0 => Float32x2];
+        static ATTRIBS: [wgpu::VertexAttribute; 4]  = wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32x2,
+            2 => Float32,
+            3 => Unorm8x4,
+        ];
         
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
             attributes: &ATTRIBS,
         }
@@ -253,13 +593,8 @@ impl DefaultRenderer {
     pub fn new(state: &State) -> Self {
         let proj_buffer = state.device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Projection buffer"),
-            size: std::mem::size_of::<[f32; 16]>() as u64,
-            mapped_at_creation: false,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-        let color_buffer = state.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Color buffer"),
-            size: std::mem::size_of::<[f32; 4]>() as u64,
+            // The projection matrix and the pixel size, padded to 16 bytes.
+            size: std::mem::size_of::<[f32; 20]>() as u64,
             mapped_at_creation: false,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
@@ -267,16 +602,6 @@ impl DefaultRenderer {
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
                     visibility: wgpu::ShaderStages::VERTEX,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
@@ -293,10 +618,6 @@ impl DefaultRenderer {
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: color_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
                     resource: proj_buffer.as_entire_binding(),
                 },
             ],
@@ -329,7 +650,7 @@ impl DefaultRenderer {
                 })]
             }),
             primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: None,
@@ -364,11 +685,261 @@ impl DefaultRenderer {
             effects_compute_pipeline,
             effects_buffer_bytes_per_row,
 
-            color_buffer,
             proj_buffer,
             bind_group,
-            graphs: Vec::new(),
-            tips: Vec::new(),
+            objects: Vec::new(),
+            curves: Batch::new(state),
+            points: Batch::new(state),
+            tips: Batch::new(state),
+            tessellated: 0,
+            view_rect: WorldRect::zero(),
+            pixel_size: 1.0,
+            graphs_pixel_size: 1.0,
+            view_changed: false,
+            scene_revision: None,
+        }
+    }
+}
+/// Keeps the dashes of a pattern of dash and gap lengths along each subpath of `path`.
+fn dash_path(path: &Path, [dash, gap]: [f32; 2], tolerance: f32) -> Path {
+    use lyon::path::{ PathEvent, iterator::PathIterator, math::Point };
+
+    struct Dasher {
+        builder: lyon::path::path::Builder,
+        pattern: [f32; 2],
+        // Distance travelled in the current period.
+        phase: f32,
+        drawing: bool,
+    }
+
+    impl Dasher {
+        fn line(&mut self, from: Point, to: Point) {
+            let [dash, gap] = self.pattern;
+            let length = (to - from).length();
+            let mut pos = 0.0;
+            while pos < length {
+                let in_dash = self.phase < dash;
+                let boundary = if in_dash { dash } else { dash + gap };
+                let reached = boundary - self.phase <= length - pos;
+                let step = if reached { boundary - self.phase } else { length - pos };
+                pos += step;
+                self.phase = if reached { boundary } else { self.phase + step };
+
+                let point = from.lerp(to, pos / length);
+                if in_dash {
+                    self.builder.line_to(point);
+                }
+                if reached {
+                    if in_dash {
+                        self.builder.end(false);
+                    } else {
+                        self.builder.begin(point);
+                        self.phase = 0.0;
+                    }
+                    self.drawing = !in_dash;
+                }
+            }
         }
     }
-}
\ No newline at end of file
+
+    let mut dasher = Dasher { builder: Path::builder(), pattern: [dash, gap], phase: 0.0, drawing: false };
+    for event in path.iter().flattened(tolerance) {
+        match event {
+            PathEvent::Begin { at } => {
+                dasher.builder.begin(at);
+                dasher.phase = 0.0;
+                dasher.drawing = true;
+            },
+            PathEvent::Line { from, to } => dasher.line(from, to),
+            PathEvent::End { last, first, close } => {
+                if close {
+                    dasher.line(last, first);
+                }
+                if dasher.drawing {
+                    dasher.builder.end(false);
+                    dasher.drawing = false;
+                }
+            },
+            _ => {},
+        }
+    }
+    dasher.builder.build()
+}
+
+#[cfg(test)]
+mod test_renderer {
+    use super::*;
+    use crate::{
+        app::{ self, System },
+        scene_system::SceneSystem,
+    };
+
+    #[test]
+    fn test_dash_path() {
+        use lyon::path::{ PathEvent, math::point };
+
+        let mut builder = Path::builder();
+        builder.begin(point(0.0, 0.0));
+        builder.line_to(point(10.0, 0.0));
+        builder.end(false);
+
+        let dashes: Vec<_> = dash_path(&builder.build(), [2.0, 1.0], 0.1).iter().filter_map(|event| match event {
+            PathEvent::End { last, first, .. } => Some((first.x, last.x)),
+            _ => None,
+        }).collect();
+        assert_eq!(dashes, vec![(0.0, 2.0), (3.0, 5.0), (6.0, 8.0), (9.0, 10.0)]);
+
+        // Dashes run on around closed curves.
+        let mut builder = Path::builder();
+        builder.add_circle(point(0.0, 0.0), 10.0, lyon::path::Winding::Positive);
+        let dashed = dash_path(&builder.build(), [5.0, 5.0], 0.01);
+        let count = dashed.iter().filter(|event| matches!(event, PathEvent::Begin { .. })).count();
+        assert!((6..=7).contains(&count), "{}", count);
+    }
+
+    #[test]
+    #[ignore = "needs a graphics adapter"]
+    fn test_incremental() {
+        let state = State::new_headless(100, 100).unwrap();
+
+        let mut construction = Construction::new();
+        let a = construction.add(Definition::FreePoint(WorldPoint::new(0.0, 0.0)));
+        let b = construction.add(Definition::FreePoint(WorldPoint::new(100.0, 0.0)));
+        let circle = construction.add(Definition::Circle { center: a, through: b });
+        let c = construction.add(Definition::FreePoint(WorldPoint::new(0.0, 200.0)));
+        let d = construction.add(Definition::FreePoint(WorldPoint::new(100.0, 200.0)));
+        construction.add(Definition::Line { from: c, to: d });
+        construction.update();
+        let mut scene = Scene { construction, tip: Vec::new() };
+
+        let mut renderer = DefaultRenderer::new(&state);
+        let view_data = ViewData { center: WorldPoint::new(0.0, 0.0), size: ScreenSize::new(100.0, 100.0), pixel_size: 10.0 };
+        renderer.update_view(&view_data, &state);
+        renderer.init_in_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 6);
+
+        renderer.update_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 0);
+
+        // Only the moved point and the circle depending on it change.
+        scene.construction.move_point(b, WorldPoint::new(50.0, 0.0));
+        scene.construction.update();
+        renderer.update_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 2);
+
+        scene.construction.set_style(circle, Style { width: 2.0, ..Style::default() });
+        renderer.update_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 1);
+
+        // Removed objects drop their meshes.
+        scene.construction.remove(&[c]);
+        renderer.update_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 0);
+        assert_eq!(renderer.objects.iter().flatten().count(), 4);
+
+        // Panning only clips the lines again.
+        scene.construction.add(Definition::Line { from: a, to: b });
+        scene.construction.update();
+        renderer.update_scene(&scene, &state);
+        renderer.update_view(&ViewData { center: WorldPoint::new(30.0, 0.0), ..view_data }, &state);
+        renderer.update_scene(&scene, &state);
+        assert_eq!(renderer.tessellated, 1);
+    }
+
+    #[test]
+    #[ignore = "needs a graphics adapter"]
+    fn test_dense() {
+        let state = State::new_headless(200, 200).unwrap();
+
+        let mut construction = Construction::new();
+        let center = construction.add(Definition::FreePoint(WorldPoint::new(0.0, 0.0)));
+        for i in 0..2000 {
+            let angle = i as f32 * 0.01;
+            let through = construction.add(Definition::FreePoint(WorldPoint::new(50.0 * angle.cos(), 50.0 * angle.sin())));
+            construction.add(Definition::Circle { center, through });
+        }
+        construction.update();
+        let scene = Scene { construction, tip: Vec::new() };
+
+        let mut renderer = DefaultRenderer::new(&state);
+        let view_data = ViewData { center: WorldPoint::new(0.0, 0.0), size: ScreenSize::new(200.0, 200.0), pixel_size: 1.0 };
+        renderer.update_view(&view_data, &state);
+        renderer.init_in_scene(&scene, &state);
+        renderer.render(&state, state.offscreen_texture().unwrap());
+
+        // More vertices than u16 indices can address, in one draw call per batch.
+        assert!(renderer.curves.vertex_capacity > u16::MAX as usize);
+        let image = state.read_offscreen().unwrap();
+        assert!(image.pixel(150, 100)[0] > 200);
+        assert_eq!(image.pixel(125, 100), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[ignore = "needs a graphics adapter"]
+    fn test_zoom() {
+        let state = State::new_headless(100, 100).unwrap();
+
+        let mut construction = Construction::new();
+        let a = construction.add(Definition::FreePoint(WorldPoint::new(-1000.0, 0.0)));
+        let b = construction.add(Definition::FreePoint(WorldPoint::new(1000.0, 0.0)));
+        let line = construction.add(Definition::Line { from: a, to: b });
+        construction.set_style(line, Style { width: 6.0, ..Style::default() });
+        construction.update();
+        let scene = Scene { construction, tip: Vec::new() };
+
+        let mut renderer = DefaultRenderer::new(&state);
+        renderer.init_in_scene(&scene, &state);
+        // The line is 6 pixels thick whatever the zoom.
+        for pixel_size in [0.01, 1.0, 100.0] {
+            let view_data = ViewData { center: WorldPoint::new(0.0, 0.0), size: ScreenSize::new(100.0, 100.0), pixel_size };
+            renderer.update_view(&view_data, &state);
+            renderer.update_scene(&scene, &state);
+            renderer.render(&state, state.offscreen_texture().unwrap());
+
+            let image = state.read_offscreen().unwrap();
+            let thickness = (0..image.height).filter(|y| image.pixel(50, *y)[0] > 128).count();
+            assert!((5..=7).contains(&thickness), "{} at {}", thickness, pixel_size);
+        }
+    }
+
+    #[test]
+    #[ignore = "needs a graphics adapter"]
+    fn test_offscreen() {
+        let state = State::new_headless(400, 400).unwrap();
+
+        let mut construction = Construction::new();
+        let a = construction.add(Definition::FreePoint(WorldPoint::new(-200.0, 0.0)));
+        let b = construction.add(Definition::FreePoint(WorldPoint::new(200.0, 0.0)));
+        construction.add(Definition::Line { from: a, to: b });
+        let c = construction.add(Definition::FreePoint(WorldPoint::new(-200.0, 250.0)));
+        let d = construction.add(Definition::FreePoint(WorldPoint::new(200.0, 250.0)));
+        let blue = construction.add(Definition::Line { from: c, to: d });
+        construction.set_style(blue, Style { stroke: Rgba8::new(0x0000ffff), width: 20.0, ..Style::default() });
+        construction.update();
+        let scene = Scene { construction, tip: Vec::new() };
+
+        // 1000 world units fit in 400 pixels, so the line is 4 pixels wide.
+        let mut system = SceneSystem::new(scene, DefaultRenderer::new(&state));
+        system.start(&state);
+        let image = app::render_offscreen(&mut system, &state).unwrap();
+
+        assert_eq!((image.width, image.height, image.pixels.len()), (400, 400, 400 * 400 * 4));
+        // The line crosses the middle of the view in red, and the rest is background.
+        let column: Vec<_> = (0..image.height).map(|y| image.pixel(200, y)).collect();
+        let reddest = column.iter().max_by_key(|[r, g, b, _]| *r as i32 - (*g as i32).max(*b as i32)).unwrap();
+        assert!(reddest[0] > 200 && reddest[1] < 50 && reddest[2] < 50, "{:?}", reddest);
+        assert!((195..205).contains(&column.iter().position(|pixel| pixel == reddest).unwrap()));
+        assert_ne!(column[5], *reddest);
+        // Each object has its own style.
+        let [r, g, b, _] = image.pixel(200, 100);
+        assert!(r < 50 && g < 50 && b > 200, "{:?}", [r, g, b]);
+
+        // The PNG holds the same pixels.
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(pixels, image.pixels);
+    }
+}
@@ -1,25 +1,35 @@
 struct VertexInput {
-    @location(0) position: vec4<f32>,
+    @location(0) position: vec2<f32>,
+    @location(1) normal: vec2<f32>,
+    @location(2) width: f32,
+    @location(3) color: vec4<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
 };
 
-@group(0) @binding(0)
-var<uniform> color: vec4<f32>;
+struct View {
+    proj_mat: mat4x4<f32>,
+    pixel_size: f32,
+};
 
-@group(0) @binding(1)
-var<uniform> proj_mat: mat4x4<f32>;
+@group(0) @binding(0)
+var<uniform> view: View;
 
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
+    // Extrude in screen pixels, so that the width doesn't change with the zoom.
+    let position = model.position + model.normal * model.width * 0.5 * view.pixel_size;
+
     var out: VertexOutput;
-    out.clip_position = proj_mat * model.position;
+    out.clip_position = view.proj_mat * vec4<f32>(position, 0.0, 1.0);
+    out.color = model.color;
     return out;
 }
 
 @fragment
-fn fs_main() -> @location(0) vec4<f32> {
-    return color;
-}
\ No newline at end of file
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
@@ -7,7 +7,10 @@ use winit::event::*;
 pub struct Controller {
     pub cursor_state: CursorState,
     input_state: InputState,
-    resize_event: Option<winit::dpi::PhysicalSize<u32>>
+    resize_event: Option<winit::dpi::PhysicalSize<u32>>,
+    clicks: Vec<winit::dpi::PhysicalPosition<f64>>,
+    presses: Vec<winit::dpi::PhysicalPosition<f64>>,
+    cancel: bool,
 }
 
 impl Controller {
@@ -20,6 +23,9 @@ impl Controller {
             },
             cursor_state: CursorState::default(),
             resize_event: None,
+            clicks: Vec::new(),
+            presses: Vec::new(),
+            cancel: false,
         }
     }
 
@@ -27,6 +33,21 @@ impl Controller {
         self.resize_event
     }
 
+    /// Takes the screen positions of the left clicks since the last call.
+    pub fn take_clicks(&mut self) -> Vec<winit::dpi::PhysicalPosition<f64>> {
+        std::mem::take(&mut self.clicks)
+    }
+
+    /// Takes the screen positions where the left button went down since the last call.
+    pub fn take_presses(&mut self) -> Vec<winit::dpi::PhysicalPosition<f64>> {
+        std::mem::take(&mut self.presses)
+    }
+
+    /// Whether the current gesture was cancelled since the last call.
+    pub fn take_cancel(&mut self) -> bool {
+        std::mem::take(&mut self.cancel)
+    }
+
     pub fn point_from_screen(view_data: &ViewData, point: &winit::dpi::PhysicalPosition<f64>) -> WorldPoint {
         WorldPoint::new(
             (point.x as f32 - view_data.size.width / 2.0) * view_data.pixel_size + view_data.center.x,
@@ -54,7 +75,9 @@ impl Controller {
                 delta: MouseScrollDelta::LineDelta(_, v),
                 ..
             } => {
-                view_data.write().pixel_size *= SCALE_SPEED.powi(*v as i32);
+                // Past these scales f32 world coordinates get too coarse for the screen.
+                let pixel_size = &mut view_data.write().pixel_size;
+                *pixel_size = (*pixel_size * SCALE_SPEED.powi(*v as i32)).clamp(1e-4, 1e4);
                 true
             },
             WindowEvent::MouseInput {
@@ -63,8 +86,20 @@ impl Controller {
                 ..
             } => {
                 match button {
-                    MouseButton::Left => self.input_state.left = *state,
-                    MouseButton::Right => self.input_state.right = *state,
+                    MouseButton::Left => {
+                        match (self.input_state.left, *state) {
+                            (ElementState::Pressed, ElementState::Released) => self.clicks.push(self.cursor_state.screen_pos),
+                            (ElementState::Released, ElementState::Pressed) => self.presses.push(self.cursor_state.screen_pos),
+                            _ => {},
+                        }
+                        self.input_state.left = *state;
+                    },
+                    MouseButton::Right => {
+                        if *state == ElementState::Pressed {
+                            self.cancel = true;
+                        }
+                        self.input_state.right = *state;
+                    },
                     MouseButton::Middle => self.input_state.middle = *state,
                     _ => return false
                 }
@@ -74,12 +109,24 @@ impl Controller {
                 position,
                 ..
             } => {
+                self.cursor_state.screen_pos = *position;
                 let position = Self::point_from_screen(view_data.unchecked_read(), position);
                 *self.cursor_state.moved.get_or_insert(WorldVector::zero()) += position - self.cursor_state.pos;
                 self.cursor_state.pos = position;
                 
                 false
             },
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                    state: ElementState::Pressed,
+                    ..
+                },
+                ..
+            } => {
+                self.cancel = true;
+                true
+            },
             WindowEvent::Resized(physical_size) => {
                 self.resize_event = Some(*physical_size);
                 view_data.write().size = ScreenSize::new(physical_size.width as _, physical_size.height as _);
@@ -99,5 +146,6 @@ pub struct InputState {
 #[derive(Default)]
 pub struct CursorState {
     pub pos: WorldPoint,
+    pub screen_pos: winit::dpi::PhysicalPosition<f64>,
     pub moved: Option<WorldVector>,
 }
@@ -0,0 +1,666 @@
+use std::collections::HashMap;
+
+use json::{ object, JsonValue, JsonError::WrongType };
+
+use crate::data::{
+    prelude::*,
+    rules::{ Rules, Compass },
+    fold::{ Fold, Axiom },
+};
+use super::user_macro::UserMacro;
+
+/// Version of the scene file format written by [`to_json`].
+pub const VERSION: u32 = 7;
+
+type Migration = fn(&mut JsonValue) -> anyhow::Result<()>;
+
+/// Upgrades a document of version `i + 1` to `i + 2`. Every change of the format bumps
+/// [`VERSION`] and adds one, so that older builds refuse newer files by their version.
+const MIGRATIONS: [Migration; (VERSION - 1) as usize] = [
+    hidden_objects,
+    user_macros,
+    rule_sets,
+    neusis_and_origami,
+    rays_and_segments,
+    arcs,
+];
+
+/// Version 2 can hide objects. Everything in older files stays shown, which a missing
+/// `hidden` already means.
+fn hidden_objects(_data: &mut JsonValue) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Version 3 keeps the macros of the scene, older files have none.
+fn user_macros(data: &mut JsonValue) -> anyhow::Result<()> {
+    data["macros"] = JsonValue::new_array();
+    Ok(())
+}
+
+/// Version 4 keeps the rule set, older files use straightedge and collapsing compass.
+fn rule_sets(data: &mut JsonValue) -> anyhow::Result<()> {
+    data["rules"] = object!{ straightedge: true, compass: "collapsing" };
+    Ok(())
+}
+
+/// Version 5 adds the marked ruler and origami, which older rule sets don't allow.
+fn neusis_and_origami(data: &mut JsonValue) -> anyhow::Result<()> {
+    data["rules"]["neusis"] = false.into();
+    data["rules"]["origami"] = false.into();
+    Ok(())
+}
+
+/// Version 6 adds rays and segments. Older files don't have them, and their intersection
+/// indices stay the same since unbounded graphs keep all slots.
+fn rays_and_segments(_data: &mut JsonValue) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Version 7 adds arcs with their fill regions, which older files don't have.
+fn arcs(_data: &mut JsonValue) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// The content of a scene file.
+pub struct Document {
+    pub construction: Construction,
+    pub view_center: WorldPoint,
+    pub pixel_size: f32,
+    pub macros: Vec<UserMacro>,
+    pub rules: Rules,
+}
+
+fn color_to_string(color: Rgba8) -> String {
+    format!(
+        "0x{:08x}",
+        color.to_array().iter().fold(0, |num, v| (num << 8) | *v as u32),
+    )
+}
+
+fn string_to_color(str: &str) -> anyhow::Result<Rgba8> {
+    let num = str.strip_prefix("0x").map(|hex| u32::from_str_radix(hex, 16));
+    match num {
+        Some(Ok(num)) => Ok(Rgba8::new(num)),
+        _ => Err(WrongType(format!("color `{}`", str)).into()),
+    }
+}
+
+fn point_to_json(point: WorldPoint) -> JsonValue {
+    json::array![point.x, point.y]
+}
+
+fn get_f32(data: &JsonValue, key: &str) -> anyhow::Result<f32> {
+    data[key].as_f32().ok_or_else(|| WrongType(key.to_string()).into())
+}
+
+fn get_usize(data: &JsonValue, key: &str) -> anyhow::Result<usize> {
+    data[key].as_usize().ok_or_else(|| WrongType(key.to_string()).into())
+}
+
+fn get_str<'a>(data: &'a JsonValue, key: &str) -> anyhow::Result<&'a str> {
+    data[key].as_str().ok_or_else(|| WrongType(key.to_string()).into())
+}
+
+fn get_point(data: &JsonValue, key: &str) -> anyhow::Result<WorldPoint> {
+    match (data[key][0].as_f32(), data[key][1].as_f32()) {
+        (Some(x), Some(y)) => Ok(WorldPoint::new(x, y)),
+        _ => Err(WrongType(key.to_string()).into()),
+    }
+}
+
+fn definition_to_json(definition: &Definition) -> JsonValue {
+    match definition {
+        Definition::FreePoint(pos) => object!{ type: "free_point", pos: point_to_json(*pos) },
+        Definition::PointOn { object, param } => object!{ type: "point_on", object: object.0, param: *param },
+        Definition::Intersection { a, b, index } => object!{ type: "intersection", a: a.0, b: b.0, index: *index },
+        Definition::Line { from, to } => object!{ type: "line", from: from.0, to: to.0 },
+        Definition::Ray { from, to } => object!{ type: "ray", from: from.0, to: to.0 },
+        Definition::Segment { from, to } => object!{ type: "segment", from: from.0, to: to.0 },
+        Definition::Circle { center, through } => object!{ type: "circle", center: center.0, through: through.0 },
+        Definition::CircleRadius { center, from, to } => object!{ type: "circle_radius", center: center.0, from: from.0, to: to.0 },
+        Definition::Arc { center, from, to, region } => object!{
+            type: "arc",
+            center: center.0,
+            from: from.0,
+            to: to.0,
+            region: match region {
+                ArcRegion::Sector => "sector",
+                ArcRegion::Segment => "segment",
+            },
+        },
+        Definition::Neusis { from, to, pole, a, b, index } => object!{
+            type: "neusis", from: from.0, to: to.0, pole: pole.0, a: a.0, b: b.0, index: *index
+        },
+        Definition::Fold(fold) => object!{
+            type: "fold",
+            axiom: fold.axiom.number(),
+            inputs: fold.inputs.iter().map(|id| id.0).collect::<Vec<_>>(),
+            index: fold.index,
+        },
+    }
+}
+
+fn json_to_definition(data: &JsonValue, ids: &HashMap<usize, ObjectId>) -> anyhow::Result<Definition> {
+    let map = |value: &JsonValue, key: &str| -> anyhow::Result<ObjectId> {
+        let file_id = value.as_usize().ok_or_else(|| WrongType(key.to_string()))?;
+        ids.get(&file_id).copied().ok_or_else(|| anyhow::anyhow!("Undefined object {} in `{}`", file_id, key))
+    };
+    let id = |key: &str| map(&data[key], key);
+
+    Ok(match get_str(data, "type")? {
+        "free_point" => Definition::FreePoint(get_point(data, "pos")?),
+        "point_on" => Definition::PointOn { object: id("object")?, param: get_f32(data, "param")? },
+        "intersection" => Definition::Intersection { a: id("a")?, b: id("b")?, index: get_usize(data, "index")? },
+        "line" => Definition::Line { from: id("from")?, to: id("to")? },
+        "ray" => Definition::Ray { from: id("from")?, to: id("to")? },
+        "segment" => Definition::Segment { from: id("from")?, to: id("to")? },
+        "circle" => Definition::Circle { center: id("center")?, through: id("through")? },
+        "circle_radius" => Definition::CircleRadius { center: id("center")?, from: id("from")?, to: id("to")? },
+        "arc" => Definition::Arc {
+            center: id("center")?,
+            from: id("from")?,
+            to: id("to")?,
+            region: match get_str(data, "region")? {
+                "sector" => ArcRegion::Sector,
+                "segment" => ArcRegion::Segment,
+                other => anyhow::bail!("Unknown arc region `{}`", other),
+            },
+        },
+        "neusis" => Definition::Neusis {
+            from: id("from")?,
+            to: id("to")?,
+            pole: id("pole")?,
+            a: id("a")?,
+            b: id("b")?,
+            index: get_usize(data, "index")?,
+        },
+        "fold" => {
+            let axiom = get_usize(data, "axiom")?;
+            let axiom = Axiom::from_number(axiom).ok_or_else(|| anyhow::anyhow!("Unknown axiom {}", axiom))?;
+            let inputs = data["inputs"].members().map(|value| map(value, "inputs")).collect::<anyhow::Result<Vec<_>>>()?;
+            if inputs.len() != axiom.inputs().len() {
+                anyhow::bail!("Axiom {} takes {} inputs", axiom.number(), axiom.inputs().len());
+            }
+            Definition::Fold(Fold { axiom, inputs, index: get_usize(data, "index")? })
+        },
+        other => anyhow::bail!("Unknown object type `{}`", other),
+    })
+}
+
+fn style_to_json(style: &Style) -> JsonValue {
+    let mut data = object!{
+        stroke: color_to_string(style.stroke),
+        width: style.width,
+    };
+    if let Some([dash, gap]) = style.dash {
+        data["dash"] = json::array![dash, gap];
+    }
+    if let Some(fill) = style.fill {
+        data["fill"] = color_to_string(fill).into();
+    }
+    data
+}
+
+fn json_to_style(data: &JsonValue) -> anyhow::Result<Style> {
+    // Dash and fill are optional, a missing key means none.
+    let dash = match &data["dash"] {
+        JsonValue::Null => None,
+        dash => match (dash[0].as_f32(), dash[1].as_f32()) {
+            (Some(dash), Some(gap)) => Some([dash, gap]),
+            _ => return Err(WrongType("dash".to_string()).into()),
+        },
+    };
+    let fill = match data["fill"].is_null() {
+        true => None,
+        false => Some(string_to_color(get_str(data, "fill")?)?),
+    };
+
+    Ok(Style {
+        stroke: string_to_color(get_str(data, "stroke")?)?,
+        width: get_f32(data, "width")?,
+        dash,
+        fill,
+    })
+}
+
+fn kind_to_str(kind: ObjectKind) -> &'static str {
+    match kind {
+        ObjectKind::Point => "point",
+        ObjectKind::Line => "line",
+        ObjectKind::Circle => "circle",
+    }
+}
+
+fn str_to_kind(str: &str) -> anyhow::Result<ObjectKind> {
+    Ok(match str {
+        "point" => ObjectKind::Point,
+        "line" => ObjectKind::Line,
+        "circle" => ObjectKind::Circle,
+        other => anyhow::bail!("Unknown object kind `{}`", other),
+    })
+}
+
+fn macro_to_json(user_macro: &UserMacro) -> JsonValue {
+    object!{
+        name: user_macro.name.as_str(),
+        inputs: user_macro.inputs.iter().map(|kind| kind_to_str(*kind)).collect::<Vec<_>>(),
+        steps: user_macro.steps.iter().map(definition_to_json).collect::<Vec<_>>(),
+        outputs: user_macro.outputs.clone(),
+    }
+}
+
+fn json_to_macro(data: &JsonValue) -> anyhow::Result<UserMacro> {
+    let inputs = data["inputs"].members()
+        .map(|kind| str_to_kind(kind.as_str().ok_or_else(|| WrongType("inputs".to_string()))?))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    // Steps may only refer to the inputs and to the steps before them.
+    let mut ids: HashMap<_, _> = (0..inputs.len()).map(|slot| (slot, ObjectId(slot))).collect();
+    let mut steps = Vec::new();
+    for step in data["steps"].members() {
+        steps.push(json_to_definition(step, &ids)?);
+        ids.insert(ids.len(), ObjectId(ids.len()));
+    }
+
+    let outputs = data["outputs"].members()
+        .map(|index| index.as_usize().filter(|index| *index < steps.len()).ok_or_else(|| WrongType("outputs".to_string()).into()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if outputs.is_empty() {
+        anyhow::bail!("Macro without outputs");
+    }
+
+    Ok(UserMacro { name: get_str(data, "name")?.to_string(), inputs, steps, outputs })
+}
+
+fn rules_to_json(rules: &Rules) -> JsonValue {
+    object!{
+        straightedge: rules.straightedge,
+        compass: match rules.compass {
+            Compass::Collapsing => "collapsing",
+            Compass::Rigid => "rigid",
+        },
+        neusis: rules.neusis,
+        origami: rules.origami,
+    }
+}
+
+fn json_to_rules(data: &JsonValue) -> anyhow::Result<Rules> {
+    let straightedge = data["straightedge"].as_bool().ok_or_else(|| WrongType("straightedge".to_string()))?;
+    let compass = match get_str(data, "compass")? {
+        "collapsing" => Compass::Collapsing,
+        "rigid" => Compass::Rigid,
+        other => anyhow::bail!("Unknown compass `{}`", other),
+    };
+    let neusis = data["neusis"].as_bool().ok_or_else(|| WrongType("neusis".to_string()))?;
+    let origami = data["origami"].as_bool().ok_or_else(|| WrongType("origami".to_string()))?;
+    Ok(Rules { straightedge, compass, neusis, origami })
+}
+
+fn json_to_macros(data: &JsonValue) -> anyhow::Result<Vec<UserMacro>> {
+    if !data.is_array() {
+        return Err(WrongType("macros".to_string()).into());
+    }
+    data.members().map(json_to_macro).collect()
+}
+
+pub fn to_json(construction: &Construction, macros: &[UserMacro], rules: &Rules, view_data: &ViewData) -> JsonValue {
+    let objects: Vec<JsonValue> = construction.iter().map(|(id, object)| {
+        let mut data = definition_to_json(&object.definition);
+        data["id"] = id.0.into();
+        data["style"] = style_to_json(&object.style);
+        if let Some(label) = &object.label {
+            data["label"] = label.as_str().into();
+        }
+        if object.hidden {
+            data["hidden"] = true.into();
+        }
+        data
+    }).collect();
+
+    object!{
+        version: VERSION,
+        view: {
+            center: point_to_json(view_data.center),
+            pixel_size: view_data.pixel_size,
+        },
+        objects: objects,
+        macros: macros.iter().map(macro_to_json).collect::<Vec<_>>(),
+        rules: rules_to_json(rules),
+    }
+}
+
+/// Upgrades `data` to the current version in place.
+pub fn migrate(data: &mut JsonValue) -> anyhow::Result<()> {
+    upgrade(data, &MIGRATIONS)
+}
+
+/// Runs the `migrations` from the version of `data` on, up to the version they lead to.
+fn upgrade(data: &mut JsonValue, migrations: &[Migration]) -> anyhow::Result<()> {
+    let latest = migrations.len() as u32 + 1;
+    let version = data["version"].as_u32().ok_or_else(|| WrongType("version".to_string()))?;
+    if version == 0 || version > latest {
+        anyhow::bail!("Unsupported scene file version {}", version);
+    }
+
+    for migration in &migrations[(version - 1) as usize..] {
+        migration(data)?;
+    }
+    data["version"] = latest.into();
+    Ok(())
+}
+
+pub fn from_json(mut data: JsonValue) -> anyhow::Result<Document> {
+    migrate(&mut data)?;
+
+    // Ids are compacted on load, so references are mapped from the file ids.
+    let mut construction = Construction::new();
+    let mut ids = HashMap::new();
+    for object in data["objects"].members() {
+        let id = construction.add(json_to_definition(object, &ids)?);
+        construction.set_style(id, json_to_style(&object["style"])?);
+        construction.set_label(id, object["label"].as_str().map(String::from));
+        construction.set_hidden(id, object["hidden"].as_bool().unwrap_or(false));
+        ids.insert(get_usize(object, "id")?, id);
+    }
+
+    Ok(Document {
+        construction,
+        view_center: get_point(&data["view"], "center")?,
+        pixel_size: get_f32(&data["view"], "pixel_size")?,
+        macros: json_to_macros(&data["macros"])?,
+        rules: json_to_rules(&data["rules"])?,
+    })
+}
+
+pub fn save(construction: &Construction, macros: &[UserMacro], rules: &Rules, view_data: &ViewData, path: &std::path::Path) -> anyhow::Result<()> {
+    std::fs::write(path, to_json(construction, macros, rules, view_data).pretty(4))?;
+    Ok(())
+}
+
+pub fn load(path: &std::path::Path) -> anyhow::Result<Document> {
+    from_json(json::parse(std::fs::read_to_string(path)?.as_str())?)
+}
+
+/// The configuration directory of the user for this application.
+pub fn config_dir() -> anyhow::Result<std::path::PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty()).map(std::path::PathBuf::from);
+    let base = if cfg!(windows) {
+        var("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+    };
+    Ok(base.ok_or_else(|| anyhow::anyhow!("No configuration directory of the user"))?.join("build-geometry"))
+}
+
+/// Loads the file of the user at `path` with `load`, falling back to the default if it can't.
+/// Gives the path back to save to only if the file was loaded, and otherwise the error,
+/// so that a file which couldn't be read is never saved over.
+pub fn load_or_default<T: Default>(
+    path: anyhow::Result<std::path::PathBuf>,
+    load: impl FnOnce(&std::path::Path) -> anyhow::Result<T>,
+) -> (T, anyhow::Result<std::path::PathBuf>) {
+    match path.and_then(|path| load(&path).map(|value| (value, path))) {
+        Ok((value, path)) => (value, Ok(path)),
+        Err(e) => (T::default(), Err(e)),
+    }
+}
+
+/// The macros of the user, shared by all scenes.
+pub fn library_path() -> anyhow::Result<std::path::PathBuf> {
+    Ok(config_dir()?.join("macros.json"))
+}
+
+pub fn library_to_json(macros: &[UserMacro]) -> JsonValue {
+    object!{
+        version: VERSION,
+        macros: macros.iter().map(macro_to_json).collect::<Vec<_>>(),
+    }
+}
+
+pub fn library_from_json(data: &JsonValue) -> anyhow::Result<Vec<UserMacro>> {
+    let version = data["version"].as_u32().ok_or_else(|| WrongType("version".to_string()))?;
+    if version == 0 || version > VERSION {
+        anyhow::bail!("Unsupported macro library version {}", version);
+    }
+    json_to_macros(&data["macros"])
+}
+
+pub fn save_library(macros: &[UserMacro], path: &std::path::Path) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, library_to_json(macros).pretty(4))?;
+    Ok(())
+}
+
+/// Loads the library at `path`, a missing one is empty.
+pub fn load_library(path: &std::path::Path) -> anyhow::Result<Vec<UserMacro>> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => library_from_json(&json::parse(&text)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod test_document {
+    use super::*;
+
+    fn view() -> ViewData {
+        ViewData {
+            center: p(10.0, -20.0),
+            size: ScreenSize::new(800.0, 600.0),
+            pixel_size: 1.5,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let removed = c.add(Definition::FreePoint(p(1.0, 1.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let line = c.add(Definition::Line { from: top, to: a });
+        c.add(Definition::PointOn { object: line, param: 0.25 });
+        c.add(Definition::Ray { from: a, to: top });
+        c.add(Definition::Segment { from: b, to: top });
+        c.add(Definition::Arc { center: a, from: b, to: top, region: ArcRegion::Sector });
+        c.add(Definition::Arc { center: top, from: b, to: a, region: ArcRegion::Segment });
+        c.add(Definition::CircleRadius { center: top, from: a, to: b });
+        c.add(Definition::Neusis { from: a, to: b, pole: top, a: ca, b: line, index: 0 });
+        c.add(Definition::Fold(Fold { axiom: Axiom::PointToLine, inputs: vec![a, b, line], index: 1 }));
+        c.set_style(cb, Style { stroke: Rgba8::new(0x12345678), width: 2.5, dash: Some([4.0, 2.0]), fill: Some(Rgba8::new(0x00ff0040)) });
+        c.set_label(top, Some(String::from("C")));
+        c.set_hidden(ca, true);
+        c.remove(&[removed]);
+
+        let rules = Rules { straightedge: false, compass: Compass::Rigid, neusis: true, origami: false };
+        let doc = from_json(json::parse(&to_json(&c, &[], &rules, &view()).dump()).unwrap()).unwrap();
+        assert_eq!(doc.view_center, p(10.0, -20.0));
+        assert_eq!(doc.rules, rules);
+        assert_eq!(doc.pixel_size, 1.5);
+        assert_eq!(doc.construction.len(), c.len());
+
+        // Ids are compacted, so compare in order.
+        for ((_, loaded), (_, original)) in doc.construction.iter().zip(c.iter()) {
+            assert_eq!(loaded.graph(), original.graph());
+            assert_eq!(loaded.style, original.style);
+            assert_eq!(loaded.label, original.label);
+            assert_eq!(loaded.hidden, original.hidden);
+        }
+        assert_eq!(
+            doc.construction.iter().nth(3).unwrap().1.definition,
+            Definition::Circle { center: ObjectId(1), through: ObjectId(0) }
+        );
+
+        // Saving again gives the same document.
+        let again = from_json(to_json(&doc.construction, &[], &Rules::default(), &view())).unwrap();
+        assert_eq!(to_json(&again.construction, &[], &Rules::default(), &view()), to_json(&doc.construction, &[], &Rules::default(), &view()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data["version"] = (VERSION + 1).into();
+        assert!(from_json(data).is_err());
+
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data["rules"]["compass"] = "bent".into();
+        assert!(from_json(data).is_err());
+
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        c.add(Definition::Fold(Fold { axiom: Axiom::Perpendicular, inputs: vec![a, a], index: 0 }));
+        let mut data = to_json(&c, &[], &Rules::default(), &view());
+        data["objects"][1]["inputs"] = json::array![0];
+        assert!(from_json(data).is_err());
+
+        let data = json::parse(r#"{ "version": 1, "view": { "center": [0, 0], "pixel_size": 1 }, "objects": [
+            { "id": 0, "type": "line", "from": 3, "to": 4, "style": { "stroke": "0xff0000ff", "width": 1 } }
+        ] }"#).unwrap();
+        assert!(from_json(data).is_err());
+
+        let data = json::parse(r#"{ "version": 1, "view": { "center": [0, 0], "pixel_size": 1 }, "objects": [
+            { "id": 0, "type": "free_point", "pos": [1, 2], "style": { "stroke": "red", "width": 1 } }
+        ] }"#).unwrap();
+        assert!(from_json(data).is_err());
+
+        let data = json::parse(r#"{ "version": 1, "view": { "center": [0, 0], "pixel_size": 1 }, "objects": [
+            { "id": 0, "type": "free_point", "pos": [1, 2], "style": { "stroke": "0xff0000ff", "width": 1, "dash": 3 } }
+        ] }"#).unwrap();
+        assert!(from_json(data).is_err());
+    }
+
+    #[test]
+    fn test_migrations() {
+        fn second(data: &mut JsonValue) -> anyhow::Result<()> {
+            data["steps"].push(2)?;
+            Ok(())
+        }
+        fn third(data: &mut JsonValue) -> anyhow::Result<()> {
+            data["steps"].push(3)?;
+            Ok(())
+        }
+        let migrations: [Migration; 2] = [second, third];
+
+        // Only the migrations after the version of the file run.
+        let mut data = object!{ version: 1, steps: [] };
+        upgrade(&mut data, &migrations).unwrap();
+        assert_eq!(data, object!{ version: 3, steps: [2, 3] });
+        let mut data = object!{ version: 2, steps: [] };
+        upgrade(&mut data, &migrations).unwrap();
+        assert_eq!(data, object!{ version: 3, steps: [3] });
+
+        // Newer files are refused by their version.
+        let mut data = object!{ version: 4, steps: [] };
+        assert!(upgrade(&mut data, &migrations).unwrap_err().to_string().contains("version 4"));
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data["version"] = (VERSION + 1).into();
+        assert!(from_json(data).err().unwrap().to_string().starts_with("Unsupported scene file version"));
+    }
+
+    #[test]
+    fn test_old_versions() {
+        let data = json::parse(r#"{ "version": 1, "view": { "center": [1, 2], "pixel_size": 0.5 }, "objects": [
+            { "id": 0, "type": "free_point", "pos": [0, 0], "style": { "stroke": "0xff0000ff", "width": 1 } },
+            { "id": 1, "type": "free_point", "pos": [3, 0], "style": { "stroke": "0xff0000ff", "width": 1 } },
+            { "id": 2, "type": "circle", "center": 0, "through": 1, "style": { "stroke": "0x000000ff", "width": 2 } }
+        ] }"#).unwrap();
+        let doc = from_json(data).unwrap();
+        assert_eq!(doc.view_center, p(1.0, 2.0));
+        assert_eq!(doc.construction.len(), 3);
+        assert!(doc.construction.iter().all(|(_, object)| !object.hidden));
+        assert!(doc.macros.is_empty());
+        assert_eq!(doc.rules, Rules::default());
+
+        // Since version 3 the macros are required.
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data.remove("macros");
+        assert!(from_json(data).is_err());
+
+        // Since version 4 the rule set is required.
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data.remove("rules");
+        assert!(from_json(data).is_err());
+
+        // A rule set of version 4 doesn't allow the instruments added in version 5.
+        let data = json::parse(r#"{ "version": 4, "view": { "center": [0, 0], "pixel_size": 1 }, "objects": [],
+            "macros": [], "rules": { "straightedge": false, "compass": "rigid" } }"#).unwrap();
+        let rules = from_json(data).unwrap().rules;
+        assert_eq!(rules, Rules { straightedge: false, compass: Compass::Rigid, neusis: false, origami: false });
+        let mut data = to_json(&Construction::new(), &[], &Rules::default(), &view());
+        data["rules"].remove("origami");
+        assert!(from_json(data).is_err());
+
+        // Newer versions only add object types, so the content of a version 5 file stays.
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(3.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let line = c.add(Definition::Line { from: a, to: b });
+        c.add(Definition::Intersection { a: ca, b: line, index: 1 });
+        let mut data = to_json(&c, &[], &Rules::default(), &view());
+        data["version"] = 5.into();
+        let mut migrated = data.clone();
+        migrate(&mut migrated).unwrap();
+        data["version"] = VERSION.into();
+        assert_eq!(migrated, data);
+
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(3.0, 0.0)));
+        c.add(Definition::Segment { from: a, to: b });
+        let mut data = to_json(&c, &[], &Rules::default(), &view());
+        data["version"] = 6.into();
+        let mut migrated = data.clone();
+        migrate(&mut migrated).unwrap();
+        data["version"] = VERSION.into();
+        assert_eq!(migrated, data);
+    }
+
+    #[test]
+    fn test_macros() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+        let recorded = UserMacro::record("Apex", &c, &[a, b], &[top]).unwrap();
+
+        let doc = from_json(to_json(&c, std::slice::from_ref(&recorded), &Rules::default(), &view())).unwrap();
+        assert_eq!(doc.macros, vec![recorded.clone()]);
+        assert_eq!(library_from_json(&library_to_json(&doc.macros)).unwrap(), doc.macros);
+
+        // Steps can't refer to the steps after them.
+        let mut data = library_to_json(&doc.macros);
+        data["macros"][0]["steps"][0]["through"] = 3.into();
+        assert!(library_from_json(&data).is_err());
+
+        let mut data = library_to_json(&doc.macros);
+        data["macros"][0]["outputs"] = json::array![5];
+        assert!(library_from_json(&data).is_err());
+
+        // The configuration directory is created with the first saved library.
+        let dir = std::env::temp_dir().join(format!("build-geometry-test-{}", std::process::id()));
+        let path = dir.join("config").join("macros.json");
+        save_library(&doc.macros, &path).unwrap();
+        assert_eq!(load_library(&path).unwrap(), doc.macros);
+        assert!(load_library(&dir.join("missing.json")).unwrap().is_empty());
+        let (library, saved) = load_or_default(Ok(path.clone()), load_library);
+        assert_eq!((library, saved.unwrap()), (doc.macros.clone(), path.clone()));
+        // A library that can't be read isn't mistaken for a missing one.
+        std::fs::write(&path, "{ \"version\": 1000, \"macros\": [] }").unwrap();
+        assert!(load_library(&path).is_err());
+        std::fs::write(&path, "{ broken").unwrap();
+        assert!(load_library(&path).is_err());
+        let (library, saved) = load_or_default(Ok(path.clone()), load_library);
+        assert!(library.is_empty() && saved.is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
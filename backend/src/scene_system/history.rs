@@ -0,0 +1,254 @@
+use crate::data::{
+    prelude::*,
+    construction::Object,
+};
+
+/// An edit of the construction. Applying a command returns the command that reverts it.
+pub enum Command {
+    /// Puts back removed objects in their slots.
+    Add(Vec<(ObjectId, Object)>),
+    /// Removes objects together with their dependents.
+    Delete(Vec<ObjectId>),
+    MovePoint { id: ObjectId, to: WorldPoint },
+    SetStyle { id: ObjectId, style: Style },
+    SetLabel { id: ObjectId, label: Option<String> },
+}
+
+impl Command {
+    pub fn apply(self, construction: &mut Construction) -> Command {
+        match self {
+            Self::Add(objects) => {
+                let ids = objects.iter().map(|(id, _)| *id).collect();
+                construction.insert(objects);
+                Self::Delete(ids)
+            },
+            Self::Delete(ids) => Self::Add(construction.remove(&ids)),
+            Self::MovePoint { id, to } => {
+                let from = match construction.get(id).map(|object| &object.definition) {
+                    Some(Definition::FreePoint(pos)) => *pos,
+                    _ => to,
+                };
+                construction.move_point(id, to);
+                Self::MovePoint { id, to: from }
+            },
+            Self::SetStyle { id, style } => {
+                let old = construction.set_style(id, style).unwrap_or(style);
+                Self::SetStyle { id, style: old }
+            },
+            Self::SetLabel { id, label } => {
+                let old = construction.set_label(id, label.clone()).unwrap_or(label);
+                Self::SetLabel { id, label: old }
+            },
+        }
+    }
+}
+
+/// Unlimited undo and redo. Both stacks hold the commands that revert the recorded edits.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Command>,
+    redo: Vec<Command>,
+    /// The interaction the last step was made in, see [`Self::execute_in`].
+    interaction: Option<u64>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `command` and records it.
+    pub fn execute(&mut self, command: Command, construction: &mut Construction) {
+        let inverse = command.apply(construction);
+        self.record(inverse);
+        construction.update();
+    }
+
+    /// Like [`Self::execute`], but merges `command` into the last step if that changed the style
+    /// or the label of the same object in the same `interaction`, such as one drag of a slider.
+    pub fn execute_in(&mut self, command: Command, interaction: u64, construction: &mut Construction) {
+        let merge = self.interaction == Some(interaction) && match (&command, self.undo.last()) {
+            (Command::SetStyle { id, .. }, Some(Command::SetStyle { id: last, .. })) => id == last,
+            (Command::SetLabel { id, .. }, Some(Command::SetLabel { id: last, .. })) => id == last,
+            _ => false,
+        };
+
+        if merge {
+            command.apply(construction);
+            construction.update();
+        } else {
+            self.execute(command, construction);
+            self.interaction = Some(interaction);
+        }
+    }
+
+    /// Records an edit that is already applied, given the command reverting it.
+    pub fn record(&mut self, inverse: Command) {
+        self.undo.push(inverse);
+        self.redo.clear();
+        self.interaction = None;
+    }
+
+    /// Records the objects added to `construction` from `first` on. A tool that failed may have
+    /// removed them again, leaving only their ids, so nothing is recorded for those.
+    pub fn record_added(&mut self, first: ObjectId, construction: &Construction) {
+        let added: Vec<_> = (first.0..construction.next_id().0)
+            .map(ObjectId)
+            .filter(|id| construction.get(*id).is_some())
+            .collect();
+        if !added.is_empty() {
+            self.record(Command::Delete(added));
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo(&mut self, construction: &mut Construction) -> bool {
+        match self.undo.pop() {
+            Some(command) => {
+                self.redo.push(command.apply(construction));
+                self.interaction = None;
+                construction.update();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, construction: &mut Construction) -> bool {
+        match self.redo.pop() {
+            Some(command) => {
+                self.undo.push(command.apply(construction));
+                self.interaction = None;
+                construction.update();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.interaction = None;
+    }
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::*;
+
+    #[test]
+    fn test_add_delete() {
+        let mut c = Construction::new();
+        let mut history = History::new();
+
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        history.record(Command::Delete(vec![a]));
+        let b = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let ab = c.add(Definition::Line { from: a, to: b });
+        history.record(Command::Delete(vec![b, ab]));
+
+        history.execute(Command::Delete(vec![b]), &mut c);
+        assert_eq!(c.len(), 1);
+
+        assert!(history.undo(&mut c));
+        assert_eq!(c.graph(ab), Some(GraphType::Line { from: p(0.0, 0.0), to: p(5.0, 0.0) }));
+        assert!(history.undo(&mut c));
+        assert!(c.get(b).is_none());
+        assert!(history.undo(&mut c));
+        assert!(c.is_empty());
+        assert!(!history.undo(&mut c));
+
+        assert!(history.redo(&mut c));
+        assert!(history.redo(&mut c));
+        assert_eq!(c.len(), 3);
+        assert!(history.redo(&mut c));
+        assert_eq!(c.len(), 1);
+        assert!(!history.redo(&mut c));
+    }
+
+    #[test]
+    fn test_move_and_style() {
+        let mut c = Construction::new();
+        let mut history = History::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(5.0, 0.0)));
+        let ab = c.add(Definition::Circle { center: a, through: b });
+
+        history.execute(Command::MovePoint { id: b, to: p(0.0, 3.0) }, &mut c);
+        assert_eq!(c.graph(ab), Some(GraphType::Circle { center: p(0.0, 0.0), radius: 3.0 }));
+
+        let red = Style::default();
+        let blue = Style { stroke: Rgba8::new(0x0000ffff), ..red };
+        let wide = Style { width: 20.0, ..blue };
+        let dashed = Style { dash: Some([4.0, 2.0]), ..wide };
+        history.execute_in(Command::SetStyle { id: ab, style: blue }, 1, &mut c);
+        history.execute_in(Command::SetStyle { id: ab, style: wide }, 1, &mut c);
+        history.execute_in(Command::SetStyle { id: ab, style: dashed }, 2, &mut c);
+        assert_eq!(c.get(ab).unwrap().style, dashed);
+
+        // The style changes of one interaction are one step.
+        assert!(history.undo(&mut c));
+        assert_eq!(c.get(ab).unwrap().style, wide);
+        assert!(history.undo(&mut c));
+        assert_eq!(c.get(ab).unwrap().style, red);
+
+        // After undoing, the same interaction goes on in a new step.
+        assert!(history.redo(&mut c));
+        history.execute_in(Command::SetStyle { id: ab, style: blue }, 1, &mut c);
+        assert!(history.undo(&mut c));
+        assert_eq!(c.get(ab).unwrap().style, wide);
+        assert!(history.undo(&mut c));
+        assert!(history.undo(&mut c));
+        assert_eq!(c.graph(ab), Some(GraphType::Circle { center: p(0.0, 0.0), radius: 5.0 }));
+
+        // Typing a label is one step.
+        history.execute_in(Command::SetLabel { id: a, label: Some(String::from("A")) }, 3, &mut c);
+        history.execute_in(Command::SetLabel { id: a, label: Some(String::from("AB")) }, 3, &mut c);
+        assert_eq!(c.get(a).unwrap().label.as_deref(), Some("AB"));
+        assert!(history.undo(&mut c));
+        assert_eq!(c.get(a).unwrap().label, None);
+        assert!(history.redo(&mut c));
+        assert_eq!(c.get(a).unwrap().label.as_deref(), Some("AB"));
+
+        // A new edit drops the redo stack.
+        history.execute(Command::MovePoint { id: a, to: p(1.0, 0.0) }, &mut c);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_failed_macro() {
+        use crate::{
+            data::fold::{ Fold, Axiom },
+            scene_system::{ macros::expand, tool::ToolKind },
+        };
+
+        let mut c = Construction::new();
+        let mut history = History::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(4.0, 0.0)));
+        let crease = c.add(Definition::Fold(Fold { axiom: Axiom::PointToPoint, inputs: vec![a, b], index: 0 }));
+        let on = c.add(Definition::PointOn { object: crease, param: 0.5 });
+        c.update();
+
+        // The parallel through a point on the crease fails after adding the points on it.
+        let next = c.next_id();
+        assert_eq!(expand(ToolKind::Parallel, &[crease, on], &mut c), None);
+        assert!(c.next_id() != next);
+        history.record_added(next, &c);
+        assert!(!history.can_undo());
+
+        let next = c.next_id();
+        expand(ToolKind::Perpendicular, &[crease, on], &mut c).unwrap();
+        history.record_added(next, &c);
+        assert!(history.undo(&mut c));
+        assert_eq!(c.len(), 4);
+    }
+}
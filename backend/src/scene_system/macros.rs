@@ -0,0 +1,244 @@
+use crate::data::{
+    prelude::*,
+    intersection::distance_to,
+};
+use super::tool::ToolKind;
+
+/// Expands a macro tool into straightedge and compass steps. The intermediate steps
+/// are hidden, so that only the result is shown. Returns `None` when the inputs are
+/// degenerate, leaving the construction as it was.
+pub fn expand(kind: ToolKind, inputs: &[ObjectId], construction: &mut Construction) -> Option<ObjectId> {
+    let next = construction.next_id();
+    let result = match (kind, inputs) {
+        (ToolKind::Midpoint, &[a, b]) => midpoint(construction, a, b),
+        (ToolKind::PerpendicularBisector, &[a, b]) => perpendicular_bisector(construction, a, b),
+        (ToolKind::AngleBisector, &[a, vertex, b]) => angle_bisector(construction, a, vertex, b),
+        (ToolKind::Perpendicular, &[line, point]) => perpendicular(construction, line, point),
+        (ToolKind::Parallel, &[line, point]) => parallel(construction, line, point),
+        _ => None,
+    };
+
+    if result.is_none() {
+        let added: Vec<_> = (next.0..construction.next_id().0).map(ObjectId).collect();
+        construction.remove(&added);
+    }
+    result
+}
+
+fn position(construction: &Construction, id: ObjectId) -> Option<WorldPoint> {
+    match construction.graph(id)? {
+        GraphType::Point(pos) => Some(pos),
+        _ => None,
+    }
+}
+
+/// Reuses an object with the same definition, or else adds a hidden one.
+pub(super) fn step(construction: &mut Construction, definition: Definition) -> ObjectId {
+    construction.find(&definition).unwrap_or_else(|| {
+        let id = construction.add(definition);
+        construction.set_hidden(id, true);
+        id
+    })
+}
+
+/// The intersection of `a` and `b` whose current position satisfies `accept`.
+fn pick<F>(construction: &mut Construction, a: ObjectId, b: ObjectId, accept: F) -> Option<ObjectId>
+where
+    F: Fn(WorldPoint) -> bool
+{
+    (0..2).find_map(|index| {
+        let definition = Definition::Intersection { a, b, index };
+        match definition.evaluate(|id| construction.graph(id), &construction.tolerance) {
+            Some(GraphType::Point(pos)) if accept(pos) => Some(definition),
+            _ => None,
+        }
+    }).map(|definition| step(construction, definition))
+}
+
+/// Two points on the perpendicular bisector of `a` and `b`.
+fn bisector_points(construction: &mut Construction, a: ObjectId, b: ObjectId) -> Option<(ObjectId, ObjectId)> {
+    let ca = step(construction, Definition::Circle { center: a, through: b });
+    let cb = step(construction, Definition::Circle { center: b, through: a });
+    let top = pick(construction, ca, cb, |_| true)?;
+    let top_pos = position(construction, top)?;
+    let distance = construction.tolerance.distance;
+    let bottom = pick(construction, ca, cb, |pos| (pos - top_pos).length() > distance)?;
+    Some((top, bottom))
+}
+
+fn midpoint(construction: &mut Construction, a: ObjectId, b: ObjectId) -> Option<ObjectId> {
+    let (top, bottom) = bisector_points(construction, a, b)?;
+    let bisector = step(construction, Definition::Line { from: top, to: bottom });
+    let line = construction.find(&Definition::Line { from: b, to: a })
+        .unwrap_or_else(|| step(construction, Definition::Line { from: a, to: b }));
+    Some(construction.add(Definition::Intersection { a: bisector, b: line, index: 0 }))
+}
+
+fn perpendicular_bisector(construction: &mut Construction, a: ObjectId, b: ObjectId) -> Option<ObjectId> {
+    let (top, bottom) = bisector_points(construction, a, b)?;
+    Some(construction.add(Definition::Line { from: top, to: bottom }))
+}
+
+/// Bisects the angle at `vertex` from `a` to `b`.
+fn angle_bisector(construction: &mut Construction, a: ObjectId, vertex: ObjectId, b: ObjectId) -> Option<ObjectId> {
+    let (vertex_pos, b_pos) = (position(construction, vertex)?, position(construction, b)?);
+    let side = step(construction, Definition::Line { from: vertex, to: b });
+    let circle = step(construction, Definition::Circle { center: vertex, through: a });
+    // The point at the distance of `a` on the side of `b`.
+    let b = pick(construction, circle, side, |pos| (pos - vertex_pos).dot(b_pos - vertex_pos) > 0.0)?;
+
+    let ca = step(construction, Definition::Circle { center: a, through: vertex });
+    let cb = step(construction, Definition::Circle { center: b, through: vertex });
+    let distance = construction.tolerance.distance;
+    let far = pick(construction, ca, cb, |pos| (pos - vertex_pos).length() > distance)?;
+    Some(construction.add(Definition::Line { from: vertex, to: far }))
+}
+
+/// The line made with the straightedge under `line` and its two points. A ray or a segment
+/// is extended by a hidden line, so that the steps can go beyond its ends. The lines of the
+/// marked ruler and the folds get two hidden points on them.
+fn line_points(construction: &mut Construction, line: ObjectId) -> Option<(ObjectId, ObjectId, ObjectId)> {
+    match construction.get(line)?.definition {
+        Definition::Line { from, to } => Some((line, from, to)),
+        Definition::Ray { from, to } | Definition::Segment { from, to } => {
+            Some((step(construction, Definition::Line { from, to }), from, to))
+        },
+        Definition::Neusis { .. } | Definition::Fold(_) => {
+            let from = step(construction, Definition::PointOn { object: line, param: 0.0 });
+            let to = step(construction, Definition::PointOn { object: line, param: 1.0 });
+            Some((line, from, to))
+        },
+        _ => None,
+    }
+}
+
+/// The perpendicular to `line` through `point`.
+fn perpendicular(construction: &mut Construction, line: ObjectId, point: ObjectId) -> Option<ObjectId> {
+    let (line, from, to) = line_points(construction, line)?;
+    let pos = position(construction, point)?;
+    let distance = construction.tolerance.distance;
+
+    if distance_to(&construction.graph(line)?, pos) <= distance {
+        // Two points on the line at the same distance from `point`, then their bisector.
+        let through = if (position(construction, from)? - pos).length() > distance { from } else { to };
+        let circle = step(construction, Definition::Circle { center: point, through });
+        let a = step(construction, Definition::Intersection { a: circle, b: line, index: 0 });
+        let b = step(construction, Definition::Intersection { a: circle, b: line, index: 1 });
+        perpendicular_bisector(construction, a, b)
+    } else {
+        // The mirror image of `point` over the line.
+        let ca = step(construction, Definition::Circle { center: from, through: point });
+        let cb = step(construction, Definition::Circle { center: to, through: point });
+        let mirror = pick(construction, ca, cb, |p| (p - pos).length() > distance)?;
+        Some(construction.add(Definition::Line { from: point, to: mirror }))
+    }
+}
+
+/// The parallel to `line` through `point`, as a side of a rhombus.
+fn parallel(construction: &mut Construction, line: ObjectId, point: ObjectId) -> Option<ObjectId> {
+    let (line, from, _) = line_points(construction, line)?;
+    let from_pos = position(construction, from)?;
+    let distance = construction.tolerance.distance;
+    if distance_to(&construction.graph(line)?, position(construction, point)?) <= distance {
+        return None;
+    }
+
+    let circle = step(construction, Definition::Circle { center: from, through: point });
+    let corner = step(construction, Definition::Intersection { a: circle, b: line, index: 0 });
+    let ca = step(construction, Definition::Circle { center: point, through: from });
+    let cb = step(construction, Definition::Circle { center: corner, through: from });
+    let last = pick(construction, ca, cb, |pos| (pos - from_pos).length() > distance)?;
+    Some(construction.add(Definition::Line { from: point, to: last }))
+}
+
+#[cfg(test)]
+mod test_macros {
+    use super::*;
+    use crate::data::fold::{ Fold, Axiom };
+
+    fn line(c: &Construction, id: ObjectId) -> (WorldPoint, WorldVector) {
+        match c.graph(id) {
+            Some(GraphType::Line { from, to }) => (from, (to - from).normalize()),
+            other => panic!("Expected line, got {:?}", other),
+        }
+    }
+
+    fn visible(c: &Construction) -> usize {
+        c.iter().filter(|(_, object)| !object.hidden).count()
+    }
+
+    #[test]
+    fn test_bisectors() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(4.0, 2.0)));
+
+        let m = expand(ToolKind::Midpoint, &[a, b], &mut c).unwrap();
+        assert!((position(&c, m).unwrap() - p(2.0, 1.0)).length() < 1e-4);
+        assert_eq!(visible(&c), 3);
+
+        // The circles are shared with the midpoint.
+        let len = c.len();
+        let bisector = expand(ToolKind::PerpendicularBisector, &[a, b], &mut c).unwrap();
+        assert_eq!(c.len(), len + 1);
+        let (_, dir) = line(&c, bisector);
+        assert!(dir.dot(p(4.0, 2.0) - p(0.0, 0.0)).abs() < 1e-4);
+        assert!(distance_to(&c.graph(bisector).unwrap(), p(2.0, 1.0)) < 1e-4);
+
+        let v = c.add(Definition::FreePoint(p(1.0, 1.0)));
+        let x = c.add(Definition::FreePoint(p(3.0, 1.0)));
+        let y = c.add(Definition::FreePoint(p(1.0, 5.0)));
+        let angle = expand(ToolKind::AngleBisector, &[x, v, y], &mut c).unwrap();
+        let (origin, dir) = line(&c, angle);
+        assert!((origin - p(1.0, 1.0)).length() < 1e-4);
+        assert!((dir.x - dir.y).abs() < 1e-4 && dir.x > 0.0);
+    }
+
+    #[test]
+    fn test_through_point() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(4.0, 0.0)));
+        let ab = c.add(Definition::Line { from: a, to: b });
+        let off = c.add(Definition::FreePoint(p(1.0, 3.0)));
+        let on = c.add(Definition::PointOn { object: ab, param: 0.75 });
+
+        for point in [off, on] {
+            let perpendicular = expand(ToolKind::Perpendicular, &[ab, point], &mut c).unwrap();
+            let (_, dir) = line(&c, perpendicular);
+            assert!(dir.x.abs() < 1e-4);
+            assert!(distance_to(&c.graph(perpendicular).unwrap(), position(&c, point).unwrap()) < 1e-4);
+        }
+
+        let parallel = expand(ToolKind::Parallel, &[ab, off], &mut c).unwrap();
+        let (_, dir) = line(&c, parallel);
+        assert!(dir.y.abs() < 1e-4);
+        assert!(distance_to(&c.graph(parallel).unwrap(), p(1.0, 3.0)) < 1e-4);
+
+        // The parallel through a point on the line is the line itself.
+        let len = c.len();
+        assert_eq!(expand(ToolKind::Parallel, &[ab, on], &mut c), None);
+        assert_eq!(c.len(), len);
+    }
+
+    #[test]
+    fn test_instrument_lines() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(4.0, 0.0)));
+        // The crease is the vertical line through (2, 0).
+        let crease = c.add(Definition::Fold(Fold { axiom: Axiom::PointToPoint, inputs: vec![a, b], index: 0 }));
+        let off = c.add(Definition::FreePoint(p(5.0, 3.0)));
+        c.update();
+
+        let perpendicular = expand(ToolKind::Perpendicular, &[crease, off], &mut c).unwrap();
+        let (_, dir) = line(&c, perpendicular);
+        assert!(dir.y.abs() < 1e-4);
+        assert!(distance_to(&c.graph(perpendicular).unwrap(), p(5.0, 3.0)) < 1e-4);
+
+        let parallel = expand(ToolKind::Parallel, &[crease, off], &mut c).unwrap();
+        let (_, dir) = line(&c, parallel);
+        assert!(dir.x.abs() < 1e-4);
+        assert!(distance_to(&c.graph(parallel).unwrap(), p(5.0, 3.0)) < 1e-4);
+    }
+}
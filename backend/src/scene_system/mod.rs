@@ -2,7 +2,7 @@ use winit::event::*;
 
 use crate:: {
     app::{ System, State },
-    data::prelude::*,
+    data::{ prelude::*, rules::Rules },
     renderer::Renderer,
 };
 
@@ -12,10 +12,40 @@ use controller::{ Controller, CursorState };
 mod scene;
 pub use scene::Scene;
 
+pub mod tool;
+use tool::{ Tool, ToolKind };
+
+pub mod snap;
+use snap::Snap;
+
+pub mod macros;
+
+pub mod user_macro;
+use user_macro::UserMacro;
+
+pub mod history;
+use history::{ History, Command };
+
+pub mod document;
+pub mod svg;
+
 pub struct SceneSystem {
     view_data: DirtyFlag<ViewData>,
     ctrl: Controller,
     scene: Scene,
+    tool: Tool,
+    /// The macros recorded in the scene, used by [`ToolKind::User`].
+    macros: Vec<UserMacro>,
+    /// The instruments the tools may use.
+    rules: Rules,
+    history: History,
+    selected: Option<ObjectId>,
+    // Graphs shown with the tip until the construction changes, and the revision they are for.
+    hint: Option<(u64, Vec<GraphType>)>,
+    // The free point being dragged, where it started and where it was grabbed relative to it.
+    dragging: Option<(ObjectId, WorldPoint, WorldVector)>,
+    // Set when the construction was replaced and the renderer has to start over.
+    reload: bool,
     renderer: Box<dyn Renderer>,
 }
 
@@ -25,6 +55,14 @@ impl SceneSystem {
             view_data: DirtyFlag::new(ViewData::default()),
             ctrl: Controller::new(),
             scene,
+            tool: Tool::new(ToolKind::Straightedge),
+            macros: Vec::new(),
+            rules: Rules::default(),
+            history: History::new(),
+            selected: None,
+            hint: None,
+            dragging: None,
+            reload: false,
             renderer: Box::new(renderer)
         }
     }
@@ -36,10 +74,221 @@ impl SceneSystem {
     pub fn cursor_data(&self) -> &CursorState {
         &self.ctrl.cursor_state
     }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn tool(&self) -> ToolKind {
+        self.tool.kind()
+    }
+
+    /// Changes the tool, unless the rules don't allow it.
+    pub fn set_tool(&mut self, kind: ToolKind) {
+        self.tool = match kind {
+            ToolKind::User(index) => match self.macros.get(index) {
+                Some(user_macro) if user_macro.allowed(&self.rules) => Tool::with_macro(index, user_macro.clone()),
+                _ => return,
+            },
+            kind if kind.allowed(&self.rules) => Tool::new(kind),
+            _ => return,
+        };
+        self.dragging = None;
+    }
+
+    pub fn rules(&self) -> Rules {
+        self.rules
+    }
+
+    /// Changes the rules, falling back to the compass if the tool isn't allowed anymore.
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        let allowed = match self.tool.kind() {
+            ToolKind::User(index) => self.macros[index].allowed(&rules),
+            kind => kind.allowed(&rules),
+        };
+        if !allowed {
+            self.set_tool(ToolKind::Compass);
+        }
+    }
+
+    /// Turns the exact mode of the construction on or off, see [`Construction::set_exact`].
+    pub fn set_exact(&mut self, exact: bool) {
+        self.scene.construction.set_exact(exact);
+    }
+
+    /// Shows `graphs` until the construction changes.
+    pub fn set_hint(&mut self, graphs: Vec<GraphType>) {
+        self.hint = Some((self.scene.construction.revision(), graphs));
+    }
+
+    pub fn macros(&self) -> &[UserMacro] {
+        &self.macros
+    }
+
+    /// Adds a macro to the scene and returns its index.
+    pub fn add_macro(&mut self, user_macro: UserMacro) -> usize {
+        self.macros.push(user_macro);
+        self.macros.len() - 1
+    }
+
+    pub fn remove_macro(&mut self, index: usize) {
+        if index < self.macros.len() {
+            self.macros.remove(index);
+            if matches!(self.tool.kind(), ToolKind::User(_)) {
+                self.set_tool(ToolKind::Move);
+            }
+        }
+    }
+
+    pub fn selected(&self) -> Option<ObjectId> {
+        self.selected.filter(|id| self.scene.construction.get(*id).is_some())
+    }
+
+    /// Changes the style of `id`. The changes made in one `interaction` of the UI, such as a
+    /// drag of a slider, are undone together.
+    pub fn set_style(&mut self, id: ObjectId, style: Style, interaction: u64) {
+        self.history.execute_in(Command::SetStyle { id, style }, interaction, &mut self.scene.construction);
+    }
+
+    pub fn delete(&mut self, id: ObjectId) {
+        self.history.execute(Command::Delete(vec![id]), &mut self.scene.construction);
+    }
+
+    /// Replaces the construction and the view with a loaded document.
+    pub fn open(&mut self, document: document::Document) {
+        let view_data = self.view_data.write();
+        view_data.center = document.view_center;
+        view_data.pixel_size = document.pixel_size;
+        self.macros = document.macros;
+        if matches!(self.tool.kind(), ToolKind::User(_)) {
+            self.set_tool(ToolKind::Move);
+        }
+        self.set_rules(document.rules);
+        self.reset(document.construction);
+    }
+
+    /// Replaces the construction and drops the history. The exact mode stays as it was.
+    pub fn reset(&mut self, mut construction: Construction) {
+        construction.set_exact(self.scene.construction.is_exact());
+        self.scene.construction = construction;
+        self.scene.tip.clear();
+        self.history.clear();
+        self.tool.cancel();
+        self.selected = None;
+        self.hint = None;
+        self.dragging = None;
+        self.reload = true;
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        document::save(&self.scene.construction, &self.macros, &self.rules, self.view_data.unchecked_read(), path)
+    }
+
+    /// Exports the visible part of the construction.
+    pub fn export_svg(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let view_data = self.view_data.unchecked_read();
+        svg::export(&self.scene.construction, &view_data.world_rect(), view_data.pixel_size, path)
+    }
+
+    /// Changes the label of `id`, with the changes of one `interaction` undone together.
+    pub fn set_label(&mut self, id: ObjectId, label: Option<String>, interaction: u64) {
+        self.history.execute_in(Command::SetLabel { id, label }, interaction, &mut self.scene.construction);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    pub fn undo(&mut self) -> bool {
+        self.tool.cancel();
+        self.dragging = None;
+        self.history.undo(&mut self.scene.construction)
+    }
+
+    pub fn redo(&mut self) -> bool {
+        self.tool.cancel();
+        self.dragging = None;
+        self.history.redo(&mut self.scene.construction)
+    }
+
+    fn object_at(snap: Snap) -> Option<ObjectId> {
+        match snap {
+            Snap::Point(id) | Snap::OnObject { object: id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    fn update_tool(&mut self) {
+        let view_data = self.view_data.unchecked_read();
+        let construction = &mut self.scene.construction;
+
+        if self.ctrl.take_cancel() {
+            self.tool.cancel();
+        }
+
+        if self.tool.kind() == ToolKind::Move {
+            for pos in self.ctrl.take_presses() {
+                self.selected = Self::object_at(snap::snap(construction, view_data, &pos, snap::SNAP_RADIUS, &Rules::EUCLID));
+                // The point keeps its offset from the cursor instead of jumping to it.
+                let grab = Controller::point_from_screen(view_data, &pos);
+                self.dragging = self.selected.and_then(|id| match construction.get(id)?.definition {
+                    Definition::FreePoint(start) => Some((id, start, grab - start)),
+                    _ => None,
+                });
+            }
+            if let Some((id, _, offset)) = self.dragging {
+                construction.move_point(id, self.ctrl.cursor_state.pos - offset);
+                construction.update();
+            }
+        } else {
+            self.ctrl.take_presses();
+        }
+
+        for pos in self.ctrl.take_clicks() {
+            // Lines can still be selected and deleted when the rules don't let new points use them.
+            let rules = if self.tool.kind().constructs() { self.rules } else { Rules::EUCLID };
+            let snap = snap::snap(construction, view_data, &pos, snap::SNAP_RADIUS, &rules);
+            match self.tool.kind() {
+                ToolKind::Move => if let Some((id, start, _)) = self.dragging.take() {
+                    if construction.get(id).is_some_and(|object| object.definition != Definition::FreePoint(start)) {
+                        self.history.record(Command::MovePoint { id, to: start });
+                    }
+                },
+                ToolKind::Delete => if let Some(id) = Self::object_at(snap) {
+                    self.history.execute(Command::Delete(vec![id]), construction);
+                },
+                _ => {
+                    let next = construction.next_id();
+                    self.tool.click(snap, construction);
+                    self.history.record_added(next, construction);
+                },
+            }
+        }
+
+        let cursor = snap::snap(&self.scene.construction, view_data, &self.ctrl.cursor_state.screen_pos, snap::SNAP_RADIUS, &self.rules);
+        self.scene.tip = self.tool.preview(cursor, &self.scene.construction);
+        if let Some((revision, hint)) = &self.hint {
+            if *revision == self.scene.construction.revision() {
+                self.scene.tip.extend_from_slice(hint);
+            }
+        }
+    }
 }
 
 impl System for SceneSystem {
     fn start(&mut self, state: &State) {
+        // A headless state gets no resize event, so the view starts from the target size.
+        let size = state.size();
+        let view_data = self.view_data.write();
+        view_data.size = ScreenSize::new(size.width as _, size.height as _);
+        self.renderer.update_view_in_resize(view_data, state);
+        self.view_data.clean_flag();
+
         self.renderer.init_in_scene(&self.scene, state);
     }
 
@@ -53,7 +302,14 @@ impl System for SceneSystem {
             self.view_data.clean_flag();
         }
 
-        self.renderer.update_scene(&self.scene, state);
+        self.scene.construction.update();
+        self.update_tool();
+        if self.reload {
+            self.renderer.init_in_scene(&self.scene, state);
+            self.reload = false;
+        } else {
+            self.renderer.update_scene(&self.scene, state);
+        }
 
         self.ctrl.update(&mut self.view_data);
     }
@@ -62,7 +318,7 @@ impl System for SceneSystem {
         self.ctrl.precess(event, &mut self.view_data)
     }
 
-    fn render(&mut self, state: &State, output: &wgpu::SurfaceTexture) {
+    fn render(&mut self, state: &State, output: &wgpu::Texture) {
         self.renderer.render(state, output);
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use crate::data::prelude::*;
 
 pub struct Scene {
-    pub graph: Vec<GraphType>,
+    pub construction: Construction,
     pub tip: Vec<GraphType>,
-}
\ No newline at end of file
+}
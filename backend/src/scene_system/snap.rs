@@ -0,0 +1,234 @@
+use crate::data::{
+    prelude::*,
+    intersection::{ slots, distance_to },
+    rules::Rules,
+};
+use super::controller::Controller;
+
+/// Snap radius in screen pixels.
+pub const SNAP_RADIUS: f32 = 8.0;
+
+/// What a click refers to. Snapping to existing objects keeps a reference to
+/// them, so that the result follows when the construction moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Snap {
+    Free(WorldPoint),
+    Point(ObjectId),
+    OnObject { object: ObjectId, param: f32 },
+    /// An intersection which may not be in the construction yet.
+    Intersection { a: ObjectId, b: ObjectId, index: usize },
+}
+
+impl Snap {
+    fn definition(&self) -> Option<Definition> {
+        match *self {
+            Self::Free(pos) => Some(Definition::FreePoint(pos)),
+            Self::Point(_) => None,
+            Self::OnObject { object, param } => Some(Definition::PointOn { object, param }),
+            Self::Intersection { a, b, index } => Some(Definition::Intersection { a, b, index }),
+        }
+    }
+
+    /// The position of the snapped point in the current configuration.
+    pub fn position(&self, construction: &Construction) -> Option<WorldPoint> {
+        let graph = match self {
+            Self::Point(id) => construction.graph(*id),
+            _ => self.definition()?.evaluate(|id| construction.graph(id), &construction.tolerance),
+        };
+        match graph {
+            Some(GraphType::Point(pos)) => Some(pos),
+            _ => None,
+        }
+    }
+
+    /// Returns the snapped point, adding it to the construction if needed. An intersection
+    /// is merged with a point at the same place in exact mode.
+    pub fn materialize(&self, construction: &mut Construction) -> ObjectId {
+        match (self, self.definition()) {
+            (Self::Point(id), _) => *id,
+            (Self::Intersection { .. }, Some(definition)) => construction.find(&definition)
+                .or_else(|| construction.find_point(&definition))
+                .unwrap_or_else(|| construction.add(definition)),
+            (_, Some(definition)) => construction.add(definition),
+            (_, None) => unreachable!(),
+        }
+    }
+}
+
+/// Finds what is under the cursor within `radius` screen pixels: an existing point,
+/// an intersection of two objects, an object or else empty space, in that order of priority.
+/// Objects which `rules` don't let the new points depend on are ignored.
+pub fn snap(
+    construction: &Construction,
+    view_data: &ViewData,
+    screen_pos: &winit::dpi::PhysicalPosition<f64>,
+    radius: f32,
+    rules: &Rules,
+) -> Snap {
+    let pos = Controller::point_from_screen(view_data, screen_pos);
+    let radius = radius * view_data.pixel_size;
+
+    let mut points = Vec::new();
+    let mut curves = Vec::new();
+    for (id, object) in construction.iter().filter(|(_, object)| !object.hidden) {
+        let Some(graph) = object.graph() else { continue };
+        if !rules.draws_lines() && graph.is_line() {
+            continue;
+        }
+        let distance = distance_to(&graph, pos);
+        if distance <= radius {
+            match graph {
+                GraphType::Point(_) => points.push((distance, id)),
+                _ => curves.push((distance, id, graph)),
+            }
+        }
+    }
+
+    if let Some((_, id)) = points.iter().min_by(|a, b| a.0.total_cmp(&b.0)) {
+        return Snap::Point(*id);
+    }
+
+    // The curves are in id order, so `a < b` and each intersection has one name.
+    let mut best: Option<(f32, Snap)> = None;
+    for (i, (_, a, ga)) in curves.iter().enumerate() {
+        for (_, b, gb) in curves.iter().skip(i + 1) {
+            let slots = slots(ga, gb, &construction.tolerance).unwrap_or_default();
+            for (index, p) in slots.iter().enumerate().filter_map(|(index, p)| Some((index, (*p)?))) {
+                let distance = (p - pos).length();
+                if distance <= radius && best.is_none_or(|(d, _)| distance < d) {
+                    best = Some((distance, Snap::Intersection { a: *a, b: *b, index }));
+                }
+            }
+        }
+    }
+    if let Some((_, snap)) = best {
+        return snap;
+    }
+
+    match curves.iter().min_by(|a, b| a.0.total_cmp(&b.0)) {
+        Some((_, id, GraphType::Line { from, to } | GraphType::Ray { from, to } | GraphType::Segment { from, to })) => {
+            let dir = *to - *from;
+            Snap::OnObject { object: *id, param: (pos - *from).dot(dir) / dir.square_length() }
+        },
+        Some((_, id, GraphType::Circle { center, .. } | GraphType::Arc { center, .. })) => {
+            Snap::OnObject { object: *id, param: (pos - *center).angle_from_x_axis().radians }
+        },
+        _ => Snap::Free(pos),
+    }
+}
+
+#[cfg(test)]
+mod test_snap {
+    use super::*;
+    use winit::dpi::PhysicalPosition;
+
+    // The screen is 200x200 pixels around the world origin.
+    fn view(pixel_size: f32) -> ViewData {
+        ViewData {
+            center: p(0.0, 0.0),
+            size: ScreenSize::new(200.0, 200.0),
+            pixel_size,
+        }
+    }
+
+    fn screen(x: f64, y: f64) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(x, y)
+    }
+
+    fn construction() -> (Construction, [ObjectId; 5]) {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(100.0, 0.0)));
+        let ca = c.add(Definition::Circle { center: a, through: b });
+        let cb = c.add(Definition::Circle { center: b, through: a });
+        let ab = c.add(Definition::Line { from: a, to: b });
+        (c, [a, b, ca, cb, ab])
+    }
+
+    #[test]
+    fn test_priority() {
+        let (c, [a, _, ca, cb, _]) = construction();
+        let view = view(1.0);
+
+        assert_eq!(snap(&c, &view, &screen(103.0, 98.0), SNAP_RADIUS, &Rules::EUCLID), Snap::Point(a));
+        assert_eq!(
+            snap(&c, &view, &screen(152.0, 100.0 - 86.0), SNAP_RADIUS, &Rules::EUCLID),
+            Snap::Intersection { a: ca, b: cb, index: 0 }
+        );
+        match snap(&c, &view, &screen(101.0, 2.0), SNAP_RADIUS, &Rules::EUCLID) {
+            Snap::OnObject { object, param } => {
+                assert_eq!(object, ca);
+                assert!((param - std::f32::consts::FRAC_PI_2).abs() < 0.05);
+            },
+            other => panic!("Expected object, got {:?}", other),
+        }
+        assert_eq!(snap(&c, &view, &screen(70.0, 50.0), SNAP_RADIUS, &Rules::EUCLID), Snap::Free(p(-30.0, 50.0)));
+    }
+
+    #[test]
+    fn test_radius_in_pixels() {
+        let (c, [a, ..]) = construction();
+
+        // 50 world units are 50 pixels at this zoom, and 5 pixels when zoomed out.
+        assert_eq!(snap(&c, &view(1.0), &screen(100.0, 150.0), SNAP_RADIUS, &Rules::EUCLID), Snap::Free(p(0.0, -50.0)));
+        assert_eq!(snap(&c, &view(10.0), &screen(100.0, 105.0), SNAP_RADIUS, &Rules::EUCLID), Snap::Point(a));
+    }
+
+    #[test]
+    fn test_exact() {
+        let (mut c, [a, _, ca, cb, ab]) = construction();
+        let view = view(1.0);
+
+        let top = snap(&c, &view, &screen(150.0, 100.0 - 87.0), SNAP_RADIUS, &Rules::EUCLID);
+        let id = top.materialize(&mut c);
+        assert_eq!(top.materialize(&mut c), id);
+        assert_eq!(c.get(id).unwrap().definition, Definition::Intersection { a: ca, b: cb, index: 0 });
+
+        // Once materialized, the intersection is snapped as a point.
+        assert_eq!(snap(&c, &view, &screen(150.0, 100.0 - 87.0), SNAP_RADIUS, &Rules::EUCLID), Snap::Point(id));
+
+        let on_line = snap(&c, &view, &screen(140.0, 101.0), SNAP_RADIUS, &Rules::EUCLID);
+        assert_eq!(on_line, Snap::OnObject { object: ab, param: 0.4 });
+        let on_line = on_line.materialize(&mut c);
+
+        // Snapped points follow their references.
+        c.move_point(a, p(0.0, 20.0));
+        c.update();
+        match c.graph(on_line) {
+            Some(GraphType::Point(pos)) => assert!((pos - p(40.0, 12.0)).length() < 1e-4),
+            other => panic!("Expected point, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exact_merge() {
+        for exact in [false, true] {
+            let (mut c, [a, _, ca, cb, _]) = construction();
+            c.set_exact(exact);
+            c.update();
+            let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+            let line = c.add(Definition::Line { from: a, to: top });
+            c.set_hidden(top, true);
+            c.set_hidden(cb, true);
+
+            // The hidden point isn't snapped, but the same point on other curves finds it.
+            let snapped = snap(&c, &view(1.0), &screen(150.0, 100.0 - 87.0), SNAP_RADIUS, &Rules::EUCLID);
+            assert_eq!(snapped, Snap::Intersection { a: ca, b: line, index: 1 });
+            assert_eq!(snapped.materialize(&mut c) == top, exact);
+        }
+    }
+
+    #[test]
+    fn test_compass_only() {
+        let (c, [_, _, ca, _, ab]) = construction();
+        let view = view(1.0);
+
+        // Lines are only there to look at: neither they nor their intersections are snapped.
+        assert_eq!(snap(&c, &view, &screen(140.0, 101.0), SNAP_RADIUS, &Rules::EUCLID), Snap::OnObject { object: ab, param: 0.4 });
+        assert_eq!(snap(&c, &view, &screen(140.0, 101.0), SNAP_RADIUS, &Rules::COMPASS_ONLY), Snap::Free(p(40.0, -1.0)));
+        match snap(&c, &view, &screen(0.0, 101.0), SNAP_RADIUS, &Rules::COMPASS_ONLY) {
+            Snap::OnObject { object, .. } => assert_eq!(object, ca),
+            other => panic!("Expected the circle, got {:?}", other),
+        }
+    }
+}
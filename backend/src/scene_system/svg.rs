@@ -0,0 +1,193 @@
+use std::fmt::Write;
+
+use crate::data::{ prelude::*, graph::on_circle };
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn stroke_attributes(style: &Style) -> String {
+    let [r, g, b, a] = style.stroke.to_array();
+    let mut attributes = format!(
+        r##"stroke="#{:02x}{:02x}{:02x}" stroke-opacity="{}" stroke-width="{}""##,
+        r, g, b, a as f32 / 255.0, style.width
+    );
+    if let Some([dash, gap]) = style.dash_pattern() {
+        write!(attributes, r#" stroke-dasharray="{} {}""#, dash, gap).unwrap();
+    }
+    attributes
+}
+
+fn fill_attributes(color: Rgba8) -> String {
+    let [r, g, b, a] = color.to_array();
+    format!(r##"fill="#{:02x}{:02x}{:02x}" fill-opacity="{}""##, r, g, b, a as f32 / 255.0)
+}
+
+/// Turns the construction into a standalone SVG showing the `bounds` area, with `pixel_size`
+/// world units per pixel. Lines are clipped to `bounds`.
+pub fn to_svg(construction: &Construction, bounds: &WorldRect, pixel_size: f32) -> String {
+    let size = bounds.size();
+    let font_size = size.width.max(size.height) / 40.0;
+    // Stroke widths and dashes are in screen pixels.
+    let scaled = |style: &Style| Style {
+        width: style.width * pixel_size,
+        dash: style.dash.map(|pattern| pattern.map(|length| length * pixel_size)),
+        ..*style
+    };
+    // SVG has the y axis pointing down.
+    let map = |p: WorldPoint| (p.x - bounds.min.x, bounds.max.y - p.y);
+
+    let mut shapes = String::new();
+    let mut points = String::new();
+    let mut labels = String::new();
+
+    for (_, object) in construction.iter().filter(|(_, object)| !object.hidden) {
+        let Some(graph) = object.graph() else { continue };
+        let style = &scaled(&object.style);
+
+        let anchor = match graph {
+            GraphType::Line { .. } | GraphType::Ray { .. } | GraphType::Segment { .. } => {
+                let Some((from, to)) = graph.clip(bounds) else { continue };
+                let ((x1, y1), (x2, y2)) = (map(from), map(to));
+                writeln!(
+                    shapes,
+                    r#"  <line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+                    x1, y1, x2, y2, stroke_attributes(style)
+                ).unwrap();
+                from.lerp(to, 0.5)
+            },
+            GraphType::Circle { center, radius } => {
+                let (cx, cy) = map(center);
+                writeln!(
+                    shapes,
+                    r#"  <circle cx="{}" cy="{}" r="{}" {} {}/>"#,
+                    cx, cy, radius,
+                    style.fill.map_or_else(|| String::from(r#"fill="none""#), fill_attributes),
+                    stroke_attributes(style)
+                ).unwrap();
+                center + WorldVector::new(0.0, radius)
+            },
+            GraphType::Arc { center, radius, start, end, region } => {
+                let ((cx, cy), (x1, y1), (x2, y2)) = (map(center), map(on_circle(center, radius, start)), map(on_circle(center, radius, end)));
+                // Counterclockwise in the world is clockwise on the flipped y axis.
+                let arc = format!(
+                    "A {r} {r} 0 {} 1 {} {}",
+                    (end - start > std::f32::consts::PI) as u8, x2, y2, r = radius
+                );
+                if let Some(fill) = style.fill {
+                    let d = match region {
+                        ArcRegion::Sector => format!("M {} {} L {} {} {} Z", cx, cy, x1, y1, arc),
+                        ArcRegion::Segment => format!("M {} {} {} Z", x1, y1, arc),
+                    };
+                    writeln!(shapes, r#"  <path d="{}" {} stroke="none"/>"#, d, fill_attributes(fill)).unwrap();
+                }
+                writeln!(
+                    shapes,
+                    r#"  <path d="M {} {} {}" fill="none" {}/>"#,
+                    x1, y1, arc, stroke_attributes(style)
+                ).unwrap();
+                on_circle(center, radius, (start + end) / 2.0)
+            },
+            GraphType::Point(pos) => {
+                let (cx, cy) = map(pos);
+                writeln!(
+                    points,
+                    r#"  <circle cx="{}" cy="{}" r="{}" {}/>"#,
+                    cx, cy, style.point_radius(), fill_attributes(style.stroke)
+                ).unwrap();
+                pos
+            },
+        };
+
+        if let Some(label) = &object.label {
+            let offset = style.point_radius() + style.width;
+            let (x, y) = map(anchor + WorldVector::new(offset, offset));
+            writeln!(
+                labels,
+                r#"  <text x="{}" y="{}" font-size="{}" {}>{}</text>"#,
+                x, y, font_size, fill_attributes(style.stroke), escape(label)
+            ).unwrap();
+        }
+    }
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{pw}" height="{ph}" viewBox="0 0 {w} {h}">"#, "\n",
+            "{shapes}{points}{labels}",
+            "</svg>\n",
+        ),
+        w = size.width, h = size.height, pw = size.width / pixel_size, ph = size.height / pixel_size, shapes = shapes, points = points, labels = labels,
+    )
+}
+
+pub fn export(construction: &Construction, bounds: &WorldRect, pixel_size: f32, path: &std::path::Path) -> anyhow::Result<()> {
+    std::fs::write(path, to_svg(construction, bounds, pixel_size))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_svg {
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(50.0, 0.0)));
+        let circle = c.add(Definition::Circle { center: a, through: b });
+        c.add(Definition::Line { from: a, to: b });
+        c.set_style(circle, Style { stroke: Rgba8::new(0x00ff0080), width: 2.0, ..Style::default() });
+        let line = c.add(Definition::Line { from: b, to: a });
+        c.set_style(line, Style { dash: Some([6.0, 3.0]), fill: Some(Rgba8::new(0x0000ffff)), ..Style::default() });
+        let filled = c.add(Definition::Circle { center: b, through: a });
+        c.set_style(filled, Style { fill: Some(Rgba8::new(0x0000ffff)), ..Style::default() });
+        c.set_label(a, Some(String::from("A<1>")));
+
+        let svg = to_svg(&c, &WorldRect::new(p(-100.0, -50.0), p(100.0, 150.0)), 1.0);
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 200 200">"#));
+        assert!(svg.contains(
+            r##"<circle cx="100" cy="150" r="50" fill="none" stroke="#00ff00" stroke-opacity="0.5019608" stroke-width="2"/>"##
+        ));
+        // The line is clipped to the bounds.
+        assert!(svg.contains(r#"<line x1="0" y1="150" x2="200" y2="150" "#));
+        assert!(svg.contains(r##"<circle cx="150" cy="150" r="8" fill="#ff0000" fill-opacity="1"/>"##));
+        assert!(svg.contains(r#"stroke-width="10" stroke-dasharray="6 3"/>"#));
+        assert!(svg.contains(r##"<circle cx="150" cy="150" r="50" fill="#0000ff" fill-opacity="1" stroke="#ff0000""##));
+        assert!(svg.contains(">A&lt;1&gt;</text>"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+
+        // Widths follow the zoom, so that the export looks like the screen.
+        let svg = to_svg(&c, &WorldRect::new(p(-100.0, -50.0), p(100.0, 150.0)), 2.0);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 200 200">"#));
+        assert!(svg.contains(r#"stroke-width="20" stroke-dasharray="12 6"/>"#));
+    }
+
+    #[test]
+    fn test_outside() {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 500.0)));
+        let b = c.add(Definition::FreePoint(p(50.0, 500.0)));
+        c.add(Definition::Line { from: a, to: b });
+
+        assert!(!to_svg(&c, &WorldRect::new(p(-100.0, -100.0), p(100.0, 100.0)), 1.0).contains("<line"));
+    }
+
+    #[test]
+    fn test_arc() {
+        let mut c = Construction::new();
+        let o = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let a = c.add(Definition::FreePoint(p(50.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(0.0, 50.0)));
+        let sector = c.add(Definition::Arc { center: o, from: a, to: b, region: ArcRegion::Sector });
+        c.set_style(sector, Style { fill: Some(Rgba8::new(0x0000ffff)), ..Style::default() });
+        c.add(Definition::Arc { center: o, from: b, to: a, region: ArcRegion::Segment });
+
+        let svg = to_svg(&c, &WorldRect::new(p(-100.0, -100.0), p(100.0, 100.0)), 1.0);
+        assert!(svg.contains(r##"<path d="M 100 100 L 150 100 A 50 50 0 0 1 100 50 Z" fill="#0000ff" fill-opacity="1" stroke="none"/>"##));
+        assert!(svg.contains(r#"<path d="M 150 100 A 50 50 0 0 1 100 50" fill="none" "#));
+        // The unfilled arc goes the long way round, three quarters of the circle.
+        assert!(svg.contains(r#"<path d="M 100 50 A 50 50 0 1 1 150 "#));
+        assert_eq!(svg.matches("<path").count(), 3);
+    }
+}
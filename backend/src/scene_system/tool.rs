@@ -0,0 +1,327 @@
+use crate::data::{
+    prelude::*,
+    rules::{ Rules, Compass },
+    fold::{ Fold, Axiom },
+    intersection::distance_to,
+};
+use super::{ snap::Snap, macros, user_macro::UserMacro };
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolKind {
+    /// Selects objects and drags free points.
+    Move,
+    Delete,
+    Point,
+    Straightedge,
+    /// A straightedge line ending at the first click and going on through the second.
+    Ray,
+    /// A straightedge line between the two clicks.
+    Segment,
+    Compass,
+    /// Takes the radius from the first two clicks and the center from the third.
+    RigidCompass,
+    /// An arc around the first click, from the second counterclockwise to the direction of the third.
+    Arc(ArcRegion),
+    /// Takes the length from the first two clicks, then the pole and the two curves.
+    Neusis,
+    Fold(Axiom),
+    Midpoint,
+    PerpendicularBisector,
+    /// Bisects the angle at the second click, between the first and the third.
+    AngleBisector,
+    /// Perpendicular to a line through a point.
+    Perpendicular,
+    /// Parallel to a line through a point.
+    Parallel,
+    /// A macro recorded by the user, by its index in the scene.
+    User(usize),
+}
+
+/// What a click of a tool refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Point,
+    Line,
+    Circle,
+    /// A line or a circle.
+    Curve,
+}
+
+impl From<ObjectKind> for Input {
+    fn from(kind: ObjectKind) -> Self {
+        match kind {
+            ObjectKind::Point => Self::Point,
+            ObjectKind::Line => Self::Line,
+            ObjectKind::Circle => Self::Circle,
+        }
+    }
+}
+
+impl Input {
+    fn accepts(&self, kind: ObjectKind) -> bool {
+        match self {
+            Self::Curve => kind != ObjectKind::Point,
+            input => Input::from(kind) == *input,
+        }
+    }
+}
+
+impl ToolKind {
+    pub const ALL: [ToolKind; 22] = [
+        ToolKind::Move,
+        ToolKind::Delete,
+        ToolKind::Point,
+        ToolKind::Straightedge,
+        ToolKind::Ray,
+        ToolKind::Segment,
+        ToolKind::Compass,
+        ToolKind::RigidCompass,
+        ToolKind::Arc(ArcRegion::Sector),
+        ToolKind::Arc(ArcRegion::Segment),
+        ToolKind::Neusis,
+        ToolKind::Fold(Axiom::PointToPoint),
+        ToolKind::Fold(Axiom::LineToLine),
+        ToolKind::Fold(Axiom::Perpendicular),
+        ToolKind::Fold(Axiom::PointToLine),
+        ToolKind::Fold(Axiom::TwoPointsToLines),
+        ToolKind::Fold(Axiom::PointToLinePerpendicular),
+        ToolKind::Midpoint,
+        ToolKind::PerpendicularBisector,
+        ToolKind::AngleBisector,
+        ToolKind::Perpendicular,
+        ToolKind::Parallel,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Move => "Move",
+            Self::Delete => "Delete",
+            Self::Point => "Point",
+            Self::Straightedge => "Straightedge",
+            Self::Ray => "Ray",
+            Self::Segment => "Segment",
+            Self::Compass => "Compass",
+            Self::RigidCompass => "Rigid compass",
+            Self::Arc(ArcRegion::Sector) => "Sector",
+            Self::Arc(ArcRegion::Segment) => "Circular segment",
+            Self::Neusis => "Marked ruler",
+            Self::Fold(axiom) => axiom.name(),
+            Self::Midpoint => "Midpoint",
+            Self::PerpendicularBisector => "Perpendicular bisector",
+            Self::AngleBisector => "Angle bisector",
+            Self::Perpendicular => "Perpendicular",
+            Self::Parallel => "Parallel",
+            Self::User(_) => "User macro",
+        }
+    }
+
+    /// Whether the tool adds objects to the construction.
+    pub fn constructs(&self) -> bool {
+        !matches!(self, Self::Move | Self::Delete)
+    }
+
+    /// Whether the tool expands into several straightedge and compass steps.
+    pub fn is_macro(&self) -> bool {
+        self.constructs() && !matches!(self, Self::Point | Self::Straightedge | Self::Ray | Self::Segment | Self::Compass | Self::RigidCompass | Self::Arc(_) | Self::Neusis | Self::Fold(_))
+    }
+
+    /// Whether `rules` allow the tool. The built-in macros all draw lines. User macros
+    /// are checked by [`UserMacro::allowed`].
+    pub fn allowed(&self, rules: &Rules) -> bool {
+        match self {
+            Self::RigidCompass => rules.compass == Compass::Rigid,
+            Self::Neusis => rules.neusis,
+            Self::Fold(_) => rules.origami,
+            Self::Straightedge | Self::Ray | Self::Segment => rules.straightedge,
+            _ => rules.straightedge || !self.is_macro() || matches!(self, Self::User(_)),
+        }
+    }
+
+    /// The clicks of a constructing tool. User macros get theirs from the macro.
+    pub fn inputs(&self) -> &'static [Input] {
+        match self {
+            Self::Move | Self::Delete | Self::User(_) => &[],
+            Self::Point => &[Input::Point],
+            Self::Straightedge | Self::Ray | Self::Segment | Self::Compass | Self::Midpoint | Self::PerpendicularBisector => &[Input::Point; 2],
+            Self::RigidCompass | Self::Arc(_) | Self::AngleBisector => &[Input::Point; 3],
+            Self::Perpendicular | Self::Parallel => &[Input::Line, Input::Point],
+            Self::Neusis => &[Input::Point, Input::Point, Input::Point, Input::Curve, Input::Curve],
+            Self::Fold(axiom) => match axiom {
+                Axiom::PointToPoint => &[Input::Point; 2],
+                Axiom::LineToLine => &[Input::Line; 2],
+                Axiom::Perpendicular => &[Input::Point, Input::Line],
+                Axiom::PointToLine => &[Input::Point, Input::Point, Input::Line],
+                Axiom::TwoPointsToLines => &[Input::Point, Input::Line, Input::Point, Input::Line],
+                Axiom::PointToLinePerpendicular => &[Input::Point, Input::Line, Input::Line],
+            },
+        }
+    }
+
+    /// Number of clicks needed to finish the gesture.
+    pub fn clicks(&self) -> usize {
+        match self {
+            Self::Move | Self::Delete => 1,
+            _ => self.inputs().len(),
+        }
+    }
+}
+
+/// The state machine of the construction tools.
+pub struct Tool {
+    kind: ToolKind,
+    inputs: Vec<Input>,
+    user_macro: Option<UserMacro>,
+    snaps: Vec<Snap>,
+}
+
+impl Tool {
+    pub fn new(kind: ToolKind) -> Self {
+        Self { kind, inputs: kind.inputs().to_vec(), user_macro: None, snaps: Vec::new() }
+    }
+
+    /// Applies the user macro at `index` in the scene.
+    pub fn with_macro(index: usize, user_macro: UserMacro) -> Self {
+        Self {
+            kind: ToolKind::User(index),
+            inputs: user_macro.inputs.iter().map(|kind| Input::from(*kind)).collect(),
+            user_macro: Some(user_macro),
+            snaps: Vec::new(),
+        }
+    }
+
+    /// Number of clicks needed to finish the gesture.
+    fn clicks(&self) -> usize {
+        match self.kind {
+            ToolKind::User(_) => self.inputs.len(),
+            kind => kind.clicks(),
+        }
+    }
+
+    pub fn kind(&self) -> ToolKind {
+        self.kind
+    }
+
+    /// Drops the clicks of the current gesture.
+    pub fn cancel(&mut self) {
+        self.snaps.clear();
+    }
+
+    /// Whether `snap` is a valid next click.
+    fn accepts(&self, snap: &Snap, construction: &Construction) -> bool {
+        let inputs = &self.inputs;
+        match (inputs.get(self.snaps.len()), snap) {
+            (Some(Input::Point), _) => {
+                let Some(pos) = snap.position(construction) else { return false };
+                // Points must be distinct.
+                !self.snaps.iter().zip(inputs)
+                    .filter(|(_, input)| **input == Input::Point)
+                    .filter_map(|(snap, _)| snap.position(construction))
+                    .any(|last| (pos - last).length() <= construction.tolerance.distance)
+            },
+            (Some(input), Snap::OnObject { object, .. }) => {
+                construction.get(*object).is_some_and(|object| input.accepts(object.definition.kind()))
+            },
+            _ => false,
+        }
+    }
+
+    /// Adds the result of a full set of clicks to the construction.
+    fn finish(&self, snaps: &[Snap], construction: &mut Construction) -> Option<ObjectId> {
+        let inputs: Vec<_> = snaps.iter().zip(&self.inputs).map(|(snap, input)| match (input, snap) {
+            (Input::Line | Input::Circle | Input::Curve, Snap::OnObject { object, .. }) => *object,
+            _ => snap.materialize(construction),
+        }).collect();
+
+        match self.kind {
+            ToolKind::Move | ToolKind::Delete => None,
+            ToolKind::Point => Some(inputs[0]),
+            ToolKind::Straightedge => Some(construction.add(Definition::Line { from: inputs[0], to: inputs[1] })),
+            ToolKind::Ray => Some(construction.add(Definition::Ray { from: inputs[0], to: inputs[1] })),
+            ToolKind::Segment => Some(construction.add(Definition::Segment { from: inputs[0], to: inputs[1] })),
+            ToolKind::Compass => Some(construction.add(Definition::Circle { center: inputs[0], through: inputs[1] })),
+            ToolKind::RigidCompass => Some(construction.add(Definition::CircleRadius { center: inputs[2], from: inputs[0], to: inputs[1] })),
+            ToolKind::Arc(region) => Some(construction.add(Definition::Arc { center: inputs[0], from: inputs[1], to: inputs[2], region })),
+            ToolKind::Neusis => {
+                let [from, to, pole, a, b] = inputs[..] else { return None };
+                nearest(construction, snaps.last()?, |index| Definition::Neusis { from, to, pole, a, b, index })
+            },
+            ToolKind::Fold(axiom) => {
+                nearest(construction, snaps.last()?, |index| Definition::Fold(Fold { axiom, inputs: inputs.clone(), index }))
+            },
+            ToolKind::User(_) => self.user_macro.as_ref()?.apply(&inputs, construction)?.first().copied(),
+            kind => macros::expand(kind, &inputs, construction),
+        }
+    }
+
+    /// Feeds a click to the tool. Returns the new object when the gesture is finished.
+    pub fn click(&mut self, snap: Snap, construction: &mut Construction) -> Option<ObjectId> {
+        if !self.kind.constructs() || !self.accepts(&snap, construction) {
+            return None;
+        }
+
+        self.snaps.push(snap);
+        if self.snaps.len() < self.clicks() {
+            return None;
+        }
+
+        let snaps = std::mem::take(&mut self.snaps);
+        self.finish(&snaps, construction)
+    }
+
+    /// The graphs showing the gesture in progress, with `cursor` as the next click.
+    pub fn preview(&self, cursor: Snap, construction: &Construction) -> Vec<GraphType> {
+        if !self.kind.constructs() {
+            return Vec::new();
+        }
+
+        let mut snaps = self.snaps.clone();
+        if self.accepts(&cursor, construction) {
+            snaps.push(cursor);
+        }
+
+        let mut tip: Vec<_> = snaps.iter().zip(&self.inputs).filter_map(|(snap, input)| match (input, snap) {
+            (Input::Line | Input::Circle | Input::Curve, Snap::OnObject { object, .. }) => construction.graph(*object),
+            _ => snap.position(construction).map(GraphType::Point),
+        }).collect();
+
+        if snaps.len() == self.clicks() && self.kind != ToolKind::Point {
+            match self.kind {
+                ToolKind::Straightedge => tip.push(GraphType::Line { from: point(&tip[0]), to: point(&tip[1]) }),
+                ToolKind::Ray => tip.push(GraphType::Ray { from: point(&tip[0]), to: point(&tip[1]) }),
+                ToolKind::Segment => tip.push(GraphType::Segment { from: point(&tip[0]), to: point(&tip[1]) }),
+                ToolKind::Compass => tip.push(GraphType::Circle { center: point(&tip[0]), radius: (point(&tip[1]) - point(&tip[0])).length() }),
+                ToolKind::RigidCompass => tip.push(GraphType::Circle { center: point(&tip[2]), radius: (point(&tip[1]) - point(&tip[0])).length() }),
+                // Macros are previewed by expanding them in a copy of the construction.
+                _ => {
+                    let mut construction = construction.clone();
+                    if let Some(graph) = self.finish(&snaps, &mut construction).and_then(|id| construction.graph(id)) {
+                        tip.push(graph);
+                    }
+                },
+            }
+        }
+        tip
+    }
+}
+
+/// Adds the solution passing nearest to the last click, among those numbered by `definition`.
+fn nearest<F>(construction: &mut Construction, last: &Snap, definition: F) -> Option<ObjectId>
+where
+    F: Fn(usize) -> Definition
+{
+    let pos = last.position(construction)?;
+    let solutions = (0..).map_while(|index| {
+        let definition = definition(index);
+        let graph = definition.evaluate(|id| construction.graph(id), &construction.tolerance)?;
+        Some((distance_to(&graph, pos), definition))
+    });
+    let (_, definition) = solutions.min_by(|a, b| a.0.total_cmp(&b.0))?;
+    Some(construction.add(definition))
+}
+
+fn point(graph: &GraphType) -> WorldPoint {
+    match graph {
+        GraphType::Point(pos) => *pos,
+        _ => unreachable!(),
+    }
+}
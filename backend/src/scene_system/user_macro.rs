@@ -0,0 +1,181 @@
+use std::collections::{ BTreeSet, HashMap };
+
+use crate::data::{ prelude::*, rules::Rules };
+use super::macros::step;
+
+/// A tool recorded from a construction: the steps leading from some input objects
+/// to some output objects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserMacro {
+    pub name: String,
+    pub inputs: Vec<ObjectKind>,
+    /// Definitions referring to the inputs and to the earlier steps by their position
+    /// in the inputs followed by the steps.
+    pub steps: Vec<Definition>,
+    /// The steps which are shown, by their index in `steps`.
+    pub outputs: Vec<usize>,
+}
+
+impl UserMacro {
+    /// Records everything `outputs` are made of, back to `inputs`.
+    pub fn record(name: &str, construction: &Construction, inputs: &[ObjectId], outputs: &[ObjectId]) -> anyhow::Result<Self> {
+        if outputs.is_empty() {
+            anyhow::bail!("A macro needs at least one output");
+        }
+        if let Some(id) = outputs.iter().find(|id| inputs.contains(id)) {
+            anyhow::bail!("Object {} is both an input and an output", id.0);
+        }
+
+        let kind = |id: ObjectId| construction.get(id)
+            .map(|object| object.definition.kind())
+            .ok_or_else(|| anyhow::anyhow!("Object {} doesn't exist", id.0));
+        let input_kinds = inputs.iter().map(|id| kind(*id)).collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Ids are a topological order, so the steps are sorted by id.
+        let mut needed = BTreeSet::new();
+        let mut stack = outputs.to_vec();
+        while let Some(id) = stack.pop() {
+            if inputs.contains(&id) || !needed.insert(id) {
+                continue;
+            }
+            let object = construction.get(id).ok_or_else(|| anyhow::anyhow!("Object {} doesn't exist", id.0))?;
+            if let Definition::FreePoint(_) = object.definition {
+                anyhow::bail!("The outputs depend on the free point {}, which is not an input", id.0);
+            }
+            stack.extend(object.definition.dependencies());
+        }
+
+        let slots: HashMap<_, _> = inputs.iter().chain(needed.iter())
+            .enumerate()
+            .map(|(slot, id)| (*id, ObjectId(slot)))
+            .collect();
+        let steps: Vec<_> = needed.iter()
+            .map(|id| construction.get(*id).unwrap().definition.map_ids(|id| slots[&id]))
+            .collect();
+        let outputs = outputs.iter().map(|id| slots[id].0 - inputs.len()).collect();
+
+        Ok(Self { name: name.to_string(), inputs: input_kinds, steps, outputs })
+    }
+
+    /// Whether `rules` allow the instruments of the steps, and line inputs if there are any.
+    pub fn allowed(&self, rules: &Rules) -> bool {
+        (rules.draws_lines() || !self.inputs.contains(&ObjectKind::Line))
+            && self.steps.iter().all(|step| rules.has_instrument(step))
+    }
+
+    /// Adds the steps for the given inputs, with the intermediate ones hidden.
+    /// Returns the outputs, or `None` if the inputs don't fit or the outputs don't exist
+    /// for them, leaving the construction as it was.
+    pub fn apply(&self, inputs: &[ObjectId], construction: &mut Construction) -> Option<Vec<ObjectId>> {
+        let kinds = inputs.iter().map(|id| construction.get(*id).map(|object| object.definition.kind()));
+        if inputs.len() != self.inputs.len() || !kinds.zip(&self.inputs).all(|(kind, input)| kind == Some(*input)) {
+            return None;
+        }
+
+        let next = construction.next_id();
+        let mut slots = inputs.to_vec();
+        for (index, definition) in self.steps.iter().enumerate() {
+            let definition = definition.map_ids(|slot| slots[slot.0]);
+            slots.push(match self.outputs.contains(&index) {
+                true => construction.add(definition),
+                false => step(construction, definition),
+            });
+        }
+
+        let outputs: Vec<_> = self.outputs.iter().map(|index| slots[inputs.len() + index]).collect();
+        if outputs.iter().any(|id| construction.graph(*id).is_none()) {
+            let added: Vec<_> = (next.0..construction.next_id().0).map(ObjectId).collect();
+            construction.remove(&added);
+            return None;
+        }
+        Some(outputs)
+    }
+}
+
+#[cfg(test)]
+mod test_user_macro {
+    use super::*;
+
+    /// The centroid of a triangle, as the intersection of two medians.
+    fn centroid() -> (Construction, [ObjectId; 3], ObjectId) {
+        let mut c = Construction::new();
+        let a = c.add(Definition::FreePoint(p(0.0, 0.0)));
+        let b = c.add(Definition::FreePoint(p(6.0, 0.0)));
+        let d = c.add(Definition::FreePoint(p(0.0, 6.0)));
+        let midpoint = |c: &mut Construction, a, b| {
+            let ca = c.add(Definition::Circle { center: a, through: b });
+            let cb = c.add(Definition::Circle { center: b, through: a });
+            let top = c.add(Definition::Intersection { a: ca, b: cb, index: 0 });
+            let bottom = c.add(Definition::Intersection { a: ca, b: cb, index: 1 });
+            let bisector = c.add(Definition::Line { from: top, to: bottom });
+            let side = c.add(Definition::Line { from: a, to: b });
+            c.add(Definition::Intersection { a: bisector, b: side, index: 0 })
+        };
+        let m1 = midpoint(&mut c, a, b);
+        let m2 = midpoint(&mut c, b, d);
+        let median1 = c.add(Definition::Line { from: d, to: m1 });
+        let median2 = c.add(Definition::Line { from: a, to: m2 });
+        let g = c.add(Definition::Intersection { a: median1, b: median2, index: 0 });
+        (c, [a, b, d], g)
+    }
+
+    #[test]
+    fn test_record() {
+        let (c, inputs, g) = centroid();
+        let recorded = UserMacro::record("Centroid", &c, &inputs, &[g]).unwrap();
+        assert_eq!(recorded.inputs, vec![ObjectKind::Point; 3]);
+        assert_eq!(recorded.steps.len(), c.len() - 3);
+        assert_eq!(recorded.outputs, vec![recorded.steps.len() - 1]);
+
+        let mut other = Construction::new();
+        let inputs = [(3.0, 3.0), (9.0, 3.0), (3.0, 12.0)].map(|(x, y)| other.add(Definition::FreePoint(p(x, y))));
+        let outputs = recorded.apply(&inputs, &mut other).unwrap();
+        match other.graph(outputs[0]) {
+            Some(GraphType::Point(pos)) => assert!((pos - p(5.0, 6.0)).length() < 1e-3),
+            graph => panic!("Expected point, got {:?}", graph),
+        }
+        assert_eq!(other.iter().filter(|(_, object)| !object.hidden).count(), 4);
+
+        // Applying it again reuses the hidden steps.
+        let len = other.len();
+        recorded.apply(&inputs, &mut other).unwrap();
+        assert_eq!(other.len(), len + 1);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let (c, [a, b, _], g) = centroid();
+        assert!(UserMacro::record("Centroid", &c, &[a, b], &[g]).is_err());
+        assert!(UserMacro::record("Centroid", &c, &[a, b], &[]).is_err());
+        assert!(UserMacro::record("Centroid", &c, &[a, g], &[g]).is_err());
+
+        // A macro taking two points and making the circle through the second.
+        let circle = c.iter().find(|(_, object)| object.definition.kind() == ObjectKind::Circle).unwrap().0;
+        let recorded = UserMacro::record("Circle", &c, &[a, b], &[circle]).unwrap();
+        let mut other = Construction::new();
+        let x = other.add(Definition::FreePoint(p(0.0, 0.0)));
+        let line = other.add(Definition::Line { from: x, to: x });
+        assert_eq!(recorded.apply(&[x, line], &mut other), None);
+        assert_eq!(recorded.apply(&[x, x], &mut other), None);
+        assert_eq!(other.len(), 2);
+    }
+
+    #[test]
+    fn test_degenerate() {
+        use crate::scene_system::history::History;
+
+        let (c, inputs, g) = centroid();
+        let recorded = UserMacro::record("Centroid", &c, &inputs, &[g]).unwrap();
+
+        // The medians of three points on a line are the same line, so there is no centroid.
+        let mut other = Construction::new();
+        let mut history = History::new();
+        let inputs = [(0.0, 0.0), (4.0, 0.0), (8.0, 0.0)].map(|(x, y)| other.add(Definition::FreePoint(p(x, y))));
+        let next = other.next_id();
+        assert_eq!(recorded.apply(&inputs, &mut other), None);
+        assert!(other.next_id() != next);
+        history.record_added(next, &other);
+        assert!(!history.can_undo());
+        assert_eq!(other.len(), 3);
+    }
+}
@@ -0,0 +1,72 @@
+use backend::{
+    scene_system::{
+        SceneSystem,
+        tool::ToolKind,
+    },
+    puzzle::{ Level, Score },
+};
+
+/// What the player asked for in the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    Restart,
+    Hint,
+}
+
+pub struct LevelPanel {
+    pub message: String,
+}
+
+impl LevelPanel {
+    pub fn new() -> Self {
+        Self { message: String::new() }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, game: &mut SceneSystem, level: &Level, tools: &[ToolKind], best: Option<Score>, solved: bool) -> Option<Request> {
+        let mut request = None;
+        egui::Window::new(level.name)
+            .id(egui::Id::new("Level"))
+            .show(ctx, |ui| {
+                ui.label(level.description);
+
+                let score = level.score(&game.scene().construction);
+                ui.label(format!("{}L / {}E, target {}L / {}E", score.l, score.e, level.target.l, level.target.e));
+                if let Some(best) = best {
+                    ui.label(format!("Best {}L / {}E", best.l, best.e));
+                }
+
+                ui.separator();
+                ui.horizontal_wrapped(|ui| {
+                    tools.iter().for_each(|kind| {
+                        if ui.selectable_label(game.tool() == *kind, kind.name()).clicked() {
+                            game.set_tool(*kind);
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(game.can_undo(), egui::Button::new("Undo")).clicked() {
+                        game.undo();
+                    }
+                    if ui.add_enabled(game.can_redo(), egui::Button::new("Redo")).clicked() {
+                        game.redo();
+                    }
+                    if ui.button("Restart").clicked() {
+                        request = Some(Request::Restart);
+                    }
+                    if ui.add_enabled(!solved, egui::Button::new("Hint")).clicked() {
+                        request = Some(Request::Hint);
+                    }
+                });
+                if !self.message.is_empty() {
+                    ui.label(&self.message);
+                }
+
+                if solved {
+                    ui.separator();
+                    let stars = level.stars(Some(score));
+                    ui.colored_label(egui::Color32::GREEN, format!("Solved! {}", "★".repeat(stars) + &"☆".repeat(3 - stars)));
+                }
+            });
+        request
+    }
+}
@@ -0,0 +1,30 @@
+use backend::puzzle::{ Level, progress::Progress };
+
+pub struct LevelSelect {
+    pub open: bool,
+}
+
+impl LevelSelect {
+    pub fn new() -> Self {
+        Self { open: true }
+    }
+
+    /// Returns the index of the level to play, when one was picked.
+    pub fn ui(&mut self, ctx: &egui::Context, levels: &[Level], progress: &Progress, current: usize) -> Option<usize> {
+        let mut selected = None;
+        egui::Window::new("Levels")
+            .open(&mut self.open)
+            .show(ctx, |ui| {
+                for (index, level) in levels.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.selectable_label(index == current, format!("{}. {}", index + 1, level.name)).clicked() {
+                            selected = Some(index);
+                        }
+                        let stars = level.stars(progress.best(level.name));
+                        ui.label("★".repeat(stars) + &"☆".repeat(3 - stars));
+                    });
+                }
+            });
+        selected
+    }
+}
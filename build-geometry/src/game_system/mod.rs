@@ -0,0 +1,145 @@
+use backend::{
+    app::{
+        System,
+        App,
+        State,
+    },
+    scene_system::{
+        SceneSystem,
+        Scene,
+        document,
+    },
+    renderer::DefaultRenderer,
+    puzzle::{ self, Level, progress::Progress },
+    data::prelude::*,
+};
+
+use egui_layer::EguiLayer;
+
+mod level_select;
+mod level_panel;
+
+pub struct GameSystem {
+    egui: EguiLayer,
+
+    level_select: level_select::LevelSelect,
+    level_panel: level_panel::LevelPanel,
+
+    levels: Vec<Level>,
+    current: usize,
+    // The construction revision the goal was last checked for.
+    checked_revision: Option<u64>,
+    solved: bool,
+    progress: Progress,
+    /// The file keeping the best scores across sessions. `None` when it was unreadable,
+    /// so that the levels solved before aren't lost by saving the new ones.
+    progress_path: Option<std::path::PathBuf>,
+
+    game: Option<SceneSystem>,
+}
+
+impl GameSystem {
+    pub fn new<T>(app: &App<T>) -> Self {
+        let (progress, progress_path) = document::load_or_default(Progress::default_path(), Progress::load);
+        if let Err(e) = &progress_path {
+            eprintln!("Unable to load the progress, it won't be saved!\n{}", e);
+        }
+
+        Self {
+            egui: EguiLayer::new(app),
+            level_select: level_select::LevelSelect::new(),
+            level_panel: level_panel::LevelPanel::new(),
+            levels: puzzle::levels(),
+            current: 0,
+            checked_revision: None,
+            solved: false,
+            progress,
+            progress_path: progress_path.ok(),
+            game: None,
+        }
+    }
+
+    fn play(&mut self, index: usize) {
+        if let Some(game) = &mut self.game {
+            self.current = index;
+            self.checked_revision = None;
+            self.solved = false;
+            self.level_panel.message.clear();
+            game.set_rules(self.levels[index].rules);
+            game.reset(self.levels[index].start());
+        }
+    }
+}
+
+impl System for GameSystem {
+    fn start(&mut self, state: &State) {
+        let mut game = SceneSystem::new(
+            Scene {
+                construction: Construction::new(),
+                tip: vec![],
+            },
+            DefaultRenderer::new(state)
+        );
+        game.start(state);
+        self.game = Some(game);
+        self.play(0);
+    }
+
+    fn precess(&mut self, event: &winit::event::WindowEvent) -> bool {
+        match &mut self.game {
+            Some(game) => self.egui.precess(event, game),
+            None => false,
+        }
+    }
+
+    fn update(&mut self, state: &State) {
+        let Some(game) = &mut self.game else { return };
+
+        // Check the goal whenever the construction changed.
+        let revision = game.scene().construction.revision();
+        if self.checked_revision != Some(revision) {
+            let level = &self.levels[self.current];
+            self.solved = level.check_exact(&game.scene().construction);
+            self.checked_revision = Some(revision);
+
+            if self.solved && self.progress.record(level.name, level.score(&game.scene().construction)) {
+                if let Some(Err(e)) = self.progress_path.as_ref().map(|path| self.progress.save(path)) {
+                    eprintln!("Unable to save the progress!\n{}", e);
+                }
+            }
+        }
+
+        let mut selected = None;
+        let tools = puzzle::tools(&self.levels, &self.progress, &self.levels[self.current].rules);
+        self.egui.update(state, |ctx| {
+            selected = self.level_select.ui(ctx, &self.levels, &self.progress, self.current);
+            let level = &self.levels[self.current];
+            match self.level_panel.ui(ctx, game, level, &tools, self.progress.best(level.name), self.solved) {
+                Some(level_panel::Request::Restart) => selected = Some(self.current),
+                Some(level_panel::Request::Hint) => match level.hint(&game.scene().construction) {
+                    Ok(Some(hint)) => {
+                        game.set_hint(vec![hint]);
+                        self.level_panel.message.clear();
+                    },
+                    Ok(None) => self.level_panel.message = format!("No solution within {}E from here.", level.target.e),
+                    Err(e) => self.level_panel.message = format!("No hint found in time.\n{}", e),
+                },
+                None => {},
+            }
+        });
+
+        // Game update
+        game.update(state);
+
+        if let Some(index) = selected {
+            self.play(index);
+        }
+    }
+
+    fn render(&mut self, state: &State, output: &wgpu::Texture) {
+        if let Some(game) = &mut self.game {
+            game.render(state, output);
+            self.egui.render(state, output);
+        }
+    }
+}
@@ -1,4 +1,22 @@
+use backend::app::App;
+use winit::{
+    event_loop::EventLoop,
+    window::WindowBuilder,
+};
+
+mod game_system;
 
 fn main() {
-    
+    backend::app::init();
+
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title("Build Geometry")
+        .with_maximized(true)
+        .build(&event_loop)
+        .unwrap();
+    let app = App::new(event_loop, window);
+    let system = game_system::GameSystem::new(&app);
+
+    app.run(system);
 }
@@ -1,10 +1,18 @@
-use backend::scene_system::SceneSystem;
+use backend::{
+    scene_system::{
+        SceneSystem,
+        tool::ToolKind,
+    },
+    data::{ prelude::*, rules::Compass },
+};
 
 use std::time;
 
 pub struct GameCtrl {
     pub open: bool,
     now: time::Instant,
+    // Style and label changes made in one interaction are undone together.
+    interaction: u64,
 }
 
 impl GameCtrl {
@@ -12,6 +20,7 @@ impl GameCtrl {
         Self {
             open: true,
             now: time::Instant::now(),
+            interaction: 0,
         }
     }
 
@@ -24,6 +33,95 @@ impl GameCtrl {
 
                 ui.label(format!("fps: {:.1}", 1000.0 / self.now.elapsed().as_millis() as f32));
                 self.now = time::Instant::now();
+
+                ui.separator();
+                let mut rules = game.rules();
+                let mut rigid = rules.compass == Compass::Rigid;
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut rules.straightedge, "Straightedge");
+                    ui.checkbox(&mut rigid, "Rigid compass");
+                    ui.checkbox(&mut rules.neusis, "Marked ruler");
+                    ui.checkbox(&mut rules.origami, "Origami");
+                });
+                rules.compass = if rigid { Compass::Rigid } else { Compass::Collapsing };
+                if rules != game.rules() {
+                    game.set_rules(rules);
+                }
+                let mut exact = game.scene().construction.is_exact();
+                if ui.checkbox(&mut exact, "Exact arithmetic").changed() {
+                    game.set_exact(exact);
+                }
+                ui.horizontal_wrapped(|ui| {
+                    ToolKind::ALL.iter().for_each(|kind| {
+                        let label = egui::SelectableLabel::new(game.tool() == *kind, kind.name());
+                        if ui.add_enabled(kind.allowed(&game.rules()), label).clicked() {
+                            game.set_tool(*kind);
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(game.can_undo(), egui::Button::new("Undo")).clicked() {
+                        game.undo();
+                    }
+                    if ui.add_enabled(game.can_redo(), egui::Button::new("Redo")).clicked() {
+                        game.redo();
+                    }
+                });
+
+                if let Some(id) = game.selected() {
+                    ui.separator();
+                    let mut style = game.scene().construction.get(id).unwrap().style;
+                    let mut stroke = style.stroke.to_array();
+                    let mut changed = false;
+
+                    ui.horizontal(|ui| {
+                        changed |= ui.color_edit_button_srgba_unmultiplied(&mut stroke).changed();
+                        changed |= ui.add(egui::DragValue::new(&mut style.width).speed(0.2).clamp_range(0.0..=100.0)).changed();
+                        ui.label("Stroke");
+                    });
+
+                    let mut dashed = style.dash.is_some();
+                    let [mut dash, mut gap] = style.dash.unwrap_or([style.width * 2.0, style.width]);
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut dashed, "Dash").changed();
+                        ui.add_enabled_ui(dashed, |ui| {
+                            changed |= ui.add(egui::DragValue::new(&mut dash).speed(0.2).clamp_range(0.0..=500.0)).changed();
+                            changed |= ui.add(egui::DragValue::new(&mut gap).speed(0.2).clamp_range(0.0..=500.0)).changed();
+                        });
+                    });
+
+                    let mut filled = style.fill.is_some();
+                    let mut fill = style.fill.unwrap_or(style.stroke).to_array();
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut filled, "Fill").changed();
+                        ui.add_enabled_ui(filled, |ui| {
+                            changed |= ui.color_edit_button_srgba_unmultiplied(&mut fill).changed();
+                        });
+                    });
+
+                    if changed {
+                        style.dash = Some([dash, gap]).filter(|_| dashed);
+                        style.fill = Some(Rgba8::from_array(fill)).filter(|_| filled);
+                        style.stroke = Rgba8::from_array(stroke);
+                        game.set_style(id, style, self.interaction);
+                    }
+
+                    let mut label = game.scene().construction.get(id).unwrap().label.clone().unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        if ui.text_edit_singleline(&mut label).changed() {
+                            game.set_label(id, Some(label).filter(|label| !label.is_empty()), self.interaction);
+                        }
+                        ui.label("Label");
+                    });
+                    if ui.button("Delete").clicked() {
+                        game.delete(id);
+                    }
+                }
             });
+
+        // An interaction lasts while a widget is dragged or edited with the keyboard.
+        if !ctx.input().pointer.any_down() && ctx.memory().focus().is_none() {
+            self.interaction += 1;
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,141 @@
+use backend::{
+    scene_system::{
+        SceneSystem,
+        document,
+        tool::ToolKind,
+        user_macro::UserMacro,
+    },
+    data::prelude::*,
+};
+
+/// Records macros from the selection and manages the macros of the scene and of the library.
+pub struct MacroPanel {
+    pub open: bool,
+    name: String,
+    inputs: Vec<ObjectId>,
+    outputs: Vec<ObjectId>,
+    library: Vec<UserMacro>,
+    /// The library file shared by all scenes. `None` when it was unreadable, which leaves
+    /// the macros in it alone until the file is fixed.
+    library_path: Option<std::path::PathBuf>,
+    message: String,
+}
+
+fn ids_to_string(ids: &[ObjectId]) -> String {
+    ids.iter().map(|id| id.0.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+fn signature(user_macro: &UserMacro) -> String {
+    let inputs: Vec<_> = user_macro.inputs.iter().map(|kind| format!("{:?}", kind)).collect();
+    format!("{} ({})", user_macro.name, inputs.join(", "))
+}
+
+impl MacroPanel {
+    pub fn new() -> Self {
+        let (library, library_path) = document::load_or_default(document::library_path(), document::load_library);
+        let message = match &library_path {
+            Ok(_) => String::new(),
+            Err(e) => format!("Unable to load the macro library, changes to it won't be saved!\n{}", e),
+        };
+
+        Self {
+            open: true,
+            name: String::from("Macro"),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            library,
+            library_path: library_path.ok(),
+            message,
+        }
+    }
+
+    fn save_library(&mut self) {
+        let Some(path) = &self.library_path else {
+            self.message = String::from("The macro library wasn't loaded, so it isn't saved.");
+            return;
+        };
+        if let Err(e) = document::save_library(&self.library, path) {
+            self.message = format!("Unable to save the macro library!\n{}", e);
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, game: &mut SceneSystem) {
+        let mut open = self.open;
+        egui::Window::new("Macros")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let selected = game.selected();
+                for (label, ids) in [("Inputs", &mut self.inputs), ("Outputs", &mut self.outputs)] {
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(selected.is_some_and(|id| !ids.contains(&id)), egui::Button::new("Add selected")).clicked() {
+                            ids.extend(selected);
+                        }
+                        if ui.button("Clear").clicked() {
+                            ids.clear();
+                        }
+                        ui.label(format!("{}: {}", label, ids_to_string(ids)));
+                    });
+                }
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.name);
+                    if ui.button("Record").clicked() {
+                        match UserMacro::record(&self.name, &game.scene().construction, &self.inputs, &self.outputs) {
+                            Ok(user_macro) => {
+                                self.message = format!("Recorded `{}`", user_macro.name);
+                                game.add_macro(user_macro);
+                                self.inputs.clear();
+                                self.outputs.clear();
+                            },
+                            Err(e) => self.message = format!("Unable to record the macro!\n{}", e),
+                        }
+                    }
+                });
+
+                ui.separator();
+                ui.label("Scene");
+                let mut removed = None;
+                for (index, user_macro) in game.macros().to_vec().iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let label = egui::SelectableLabel::new(game.tool() == ToolKind::User(index), signature(user_macro));
+                        if ui.add_enabled(user_macro.allowed(&game.rules()), label).clicked() {
+                            game.set_tool(ToolKind::User(index));
+                        }
+                        if ui.button("To library").clicked() {
+                            self.library.push(user_macro.clone());
+                            self.save_library();
+                        }
+                        if ui.button("Remove").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    game.remove_macro(index);
+                }
+
+                ui.separator();
+                ui.label("Library");
+                let mut removed = None;
+                for (index, user_macro) in self.library.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(signature(user_macro));
+                        if ui.button("To scene").clicked() {
+                            game.add_macro(user_macro.clone());
+                        }
+                        if ui.button("Delete").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                }
+                if let Some(index) = removed {
+                    self.library.remove(index);
+                    self.save_library();
+                }
+
+                if !self.message.is_empty() {
+                    ui.label(&self.message);
+                }
+            });
+        self.open = open;
+    }
+}
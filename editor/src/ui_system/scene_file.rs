@@ -0,0 +1,80 @@
+use backend::scene_system::{
+    SceneSystem,
+    document,
+};
+
+use std::path::PathBuf;
+
+pub struct SceneFile {
+    pub open: bool,
+    path: Option<PathBuf>,
+    input: String,
+    message: String,
+}
+
+impl SceneFile {
+    pub fn new() -> Self {
+        Self {
+            open: true,
+            path: None,
+            input: String::from("scene.json"),
+            message: String::new(),
+        }
+    }
+
+    fn save_to(&mut self, path: PathBuf, game: &SceneSystem) {
+        match game.save(&path) {
+            Ok(()) => {
+                self.message = format!("Saved `{}`", path.display());
+                self.path = Some(path);
+            },
+            Err(e) => self.message = format!("Unable to save `{}`!\n{}", path.display(), e),
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, game: &mut SceneSystem) {
+        let mut open = self.open;
+        egui::Window::new("File")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(match &self.path {
+                    Some(path) => format!("{}", path.display()),
+                    None => String::from("Untitled"),
+                });
+                ui.text_edit_singleline(&mut self.input);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Open").clicked() {
+                        let path = PathBuf::from(&self.input);
+                        match document::load(&path) {
+                            Ok(doc) => {
+                                game.open(doc);
+                                self.message = format!("Opened `{}`", path.display());
+                                self.path = Some(path);
+                            },
+                            Err(e) => self.message = format!("Unable to open `{}`!\n{}", path.display(), e),
+                        }
+                    }
+                    if ui.button("Save").clicked() {
+                        let path = self.path.clone().unwrap_or_else(|| PathBuf::from(&self.input));
+                        self.save_to(path, game);
+                    }
+                    if ui.button("Save As").clicked() {
+                        self.save_to(PathBuf::from(&self.input), game);
+                    }
+                    if ui.button("Export SVG").clicked() {
+                        let path = PathBuf::from(&self.input).with_extension("svg");
+                        self.message = match game.export_svg(&path) {
+                            Ok(()) => format!("Exported `{}`", path.display()),
+                            Err(e) => format!("Unable to export `{}`!\n{}", path.display(), e),
+                        };
+                    }
+                });
+
+                if !self.message.is_empty() {
+                    ui.label(&self.message);
+                }
+            });
+        self.open = open;
+    }
+}
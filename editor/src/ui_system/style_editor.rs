@@ -198,16 +198,13 @@ pub fn default_style() -> egui::Style {
         Ok(style) => style,
         _ => {
             let defualt_style_json: json::JsonValue = json::parse(include_str!("egui.style.json")).unwrap();
-            match json_to_style(&defualt_style_json) {
-                Ok(style) => style,
-                _ => egui::Style::default(),
-            }
+            json_to_style(&defualt_style_json).unwrap_or_default()
         }
     }
 }
 
 pub fn load(path: &std::path::Path) -> anyhow::Result<egui::Style> {
-    Ok(json_to_style(&json::parse(std::fs::read_to_string(path)?.as_str())?)?)
+    json_to_style(&json::parse(std::fs::read_to_string(path)?.as_str())?)
 }
     
 fn json_to_style(style_data: &json::JsonValue) -> anyhow::Result<egui::Style> {
@@ -326,9 +323,8 @@ fn json_to_style(style_data: &json::JsonValue) -> anyhow::Result<egui::Style> {
         ("Heading", TextStyle::Heading),
     ]);
     for (text_style, size) in style_data["text"].entries() {
-        match text_style_map.get(text_style) {
-            Some(ts) => { style.text_styles.insert(ts.clone(),egui::FontId::proportional(size.as_f32().ok_or(WrongType(ESTR.to_string()))?)); }
-            _ => {}
+        if let Some(ts) = text_style_map.get(text_style) {
+            style.text_styles.insert(ts.clone(),egui::FontId::proportional(size.as_f32().ok_or(WrongType(ESTR.to_string()))?));
         }
     }
 
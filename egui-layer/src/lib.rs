@@ -0,0 +1,125 @@
+use backend::{
+    app::{
+        System,
+        App,
+        State,
+    },
+    scene_system::SceneSystem,
+};
+
+use egui_wgpu::renderer::{
+    RenderPass,
+    ScreenDescriptor
+};
+
+/// The egui plumbing shared by the editor and the game: the context, its window input and
+/// its render pass, drawn over a [`SceneSystem`] that gets undo and redo on Ctrl+Z and
+/// Ctrl+Shift+Z.
+pub struct EguiLayer {
+    state: egui_winit::State,
+    ctx: egui::Context,
+    rpass: RenderPass,
+
+    output_data: Option<(egui::TexturesDelta, Vec<egui::ClippedPrimitive>)>,
+    modifiers: winit::event::ModifiersState,
+}
+
+impl EguiLayer {
+    pub fn new<T>(app: &App<T>) -> Self {
+        Self {
+            state: egui_winit::State::new(&app.event_loop),
+            ctx: egui::Context::default(),
+            rpass: RenderPass::new(
+                &app.state.device,
+                app.state.config.format,
+                1
+            ),
+            output_data: None,
+            modifiers: winit::event::ModifiersState::empty(),
+        }
+    }
+
+    pub fn ctx(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    /// Passes `event` to egui, then to `game`, then handles the undo shortcuts.
+    /// Returns whether the event was used.
+    pub fn precess(&mut self, event: &winit::event::WindowEvent, game: &mut SceneSystem) -> bool {
+        use winit::event::*;
+
+        if self.state.on_event(&self.ctx, event) || game.precess(event) {
+            return true;
+        }
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+                false
+            },
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    virtual_keycode: Some(VirtualKeyCode::Z),
+                    state: ElementState::Pressed,
+                    ..
+                },
+                ..
+            } if self.modifiers.ctrl() => {
+                if self.modifiers.shift() {
+                    game.redo();
+                } else {
+                    game.undo();
+                }
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Runs a UI frame built by `run_ui` and keeps its output for [`Self::render`].
+    pub fn update(&mut self, state: &State, run_ui: impl FnOnce(&egui::Context)) {
+        // Begin to draw the UI frame.
+        let raw_input = self.state.take_egui_input(state.window());
+        let full_output = self.ctx.run(raw_input, run_ui);
+
+        // End the UI frame. We could now handle the output and draw the UI with the backend.
+        let paint_jobs = self.ctx.tessellate(full_output.shapes);
+
+        self.output_data = Some((full_output.textures_delta, paint_jobs));
+        self.state.handle_platform_output(state.window(), &self.ctx, full_output.platform_output);
+    }
+
+    /// Draws the last UI frame over `output`.
+    pub fn render(&mut self, state: &State, output: &wgpu::Texture) {
+        let view = output.create_view(&wgpu::TextureViewDescriptor::default());
+        if let Some((textures_delta, paint_jobs)) = self.output_data.take() {
+            let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("encoder"),
+            });
+
+            // Upload all resources for the GPU.
+            let screen_descriptor = ScreenDescriptor {
+                size_in_pixels: [state.config.width, state.config.height],
+                pixels_per_point: self.state.pixels_per_point(),
+            };
+            for (id, ref image_delta) in textures_delta.set {
+                self.rpass.update_texture(&state.device, &state.queue, id, image_delta);
+            }
+            self.rpass.update_buffers(&state.device, &state.queue, &paint_jobs, &screen_descriptor);
+
+            // Record all render passes.
+            self.rpass.execute(
+                &mut encoder,
+                &view,
+                &paint_jobs,
+                &screen_descriptor,
+                None,
+            );
+            // Submit the commands.
+            state.queue.submit(std::iter::once(encoder.finish()));
+
+            for id in &textures_delta.free {
+                self.rpass.free_texture(id);
+            }
+        }
+    }
+}